@@ -1,13 +1,26 @@
+use super::scheduler::PRESENCE_PROFILES_EVENT;
 use super::state::AppState;
 use crate::data::{
-    config::{Config, Mode, Referral},
-    runtime::runtime,
+    cache::SnapshotCache,
+    config::{
+        Config, Mode, PresenceProfile, PresenceTemplate, PrivacyRule, ProjectHoursSource, Referral,
+        TrackingBackend, WindowMode,
+    },
+    export::{self, ExportFormat},
+    history::{parse_date, DayTotal, History, Streaks},
+    runtime::{runtime, RuntimeReport},
+    sessions::{self, ActiveSession, SessionSummary},
+    stats::{day_projects, DayProjects, StatsCache, StatsRange, StatsReport, STATS_CACHE_DAYS},
 };
+use crate::error::FlavortimeError;
 use crate::services::{
-    discord::DiscordPresenceManager,
+    api::HackatimeApi,
+    discord::{render_presence, Activity, DiscordPresenceManager, PresenceOptions},
     flavortown,
-    hackatime::{latest_project, rolling_24h_window, Hackatime},
-    pyramid::fetch_codes,
+    hackatime::{day_windows, latest_project, tracking_window, DayWindow},
+    presence::{self, PresencePreview},
+    privacy::PrivacyRules,
+    ratelimit::EndpointStatus,
 };
 use serde::Serialize;
 use std::sync::{Mutex, MutexGuard};
@@ -16,6 +29,12 @@ use tauri::{AppHandle, Emitter, Manager, State};
 use tauri_plugin_autostart::ManagerExt;
 use tauri_plugin_updater::UpdaterExt;
 
+const MAX_SNAPSHOT_STALENESS_MINUTES: u64 = 24 * 60;
+const MAX_DAILY_GOAL_MINUTES: u64 = 24 * 60;
+const MAX_HISTORY_DAYS: u32 = 365;
+const MAX_EXPORT_DAYS: i64 = 366;
+const HISTORY_REFRESH_SECS: u64 = 15 * 60;
+
 #[derive(Serialize)]
 pub struct Status {
     pub auth_mode: String,
@@ -27,20 +46,65 @@ pub struct Status {
     pub show_time_tracking: bool,
     pub launch_at_startup: bool,
     pub app_enabled: bool,
+    pub tracking_backend: String,
+    pub wakatime_base_url: Option<String>,
+    pub wakatime_api_key_set: bool,
+    pub max_snapshot_staleness_minutes: u64,
+    pub window_mode: WindowMode,
+    pub presence_project_hours: ProjectHoursSource,
+    pub presence_template: PresenceTemplate,
+    pub presence_profiles: Vec<PresenceProfile>,
+    pub active_presence_profile: Option<String>,
+    pub privacy_rules: Vec<PrivacyRule>,
+    pub daily_goal_minutes: u64,
+    pub history_days: u32,
+}
+
+#[derive(Clone, Serialize)]
+pub struct HistorySummary {
+    pub days: Vec<DayTotal>,
+    pub streaks: Streaks,
+    pub daily_goal_minutes: u64,
+    pub fetched_at: u64,
+}
+
+#[derive(Serialize)]
+pub struct ExportSummary {
+    pub path: String,
+    pub days: usize,
+    pub entries: usize,
 }
 
 #[derive(Serialize)]
+pub struct SessionReport {
+    pub sessions: Vec<SessionSummary>,
+    pub sharing_seconds: u64,
+    pub discord_seconds: u64,
+}
+
+#[derive(Clone, Serialize)]
+pub struct PresenceProfiles {
+    pub profiles: Vec<String>,
+    pub active: Option<String>,
+}
+
+#[derive(Clone, Serialize)]
 pub struct Project {
     pub name: String,
+    pub public_name: Option<String>,
     pub hours: f64,
+    pub language: Option<String>,
 }
 
-#[derive(Serialize)]
+#[derive(Clone, Serialize)]
 pub struct HackatimeData {
     pub current_project: Option<Project>,
     pub total_hours: f64,
     pub heartbeat_idle: bool,
     pub sharing_active_seconds_total: u64,
+    pub stale: bool,
+    pub fetched_at: u64,
+    pub window_mode: WindowMode,
 }
 
 #[derive(Serialize)]
@@ -65,20 +129,32 @@ struct HackatimeSnapshot {
     current_project: Option<Project>,
     total_hours: f64,
     heartbeat_idle: bool,
+    stale: bool,
+    fetched_at: u64,
 }
 
 #[tauri::command]
-pub fn open_external(url: String) -> Result<(), String> {
+pub fn open_external(url: String) -> Result<(), FlavortimeError> {
     let url = url.trim();
     if !(url.starts_with("https://") || url.starts_with("http://")) {
-        return Err("Invalid URL".to_string());
+        return Err(FlavortimeError::InvalidInput("Invalid URL".to_string()));
     }
-    webbrowser::open(url).map_err(stringify)?;
+    webbrowser::open(url).map_err(system_error)?;
     Ok(())
 }
 
 #[tauri::command]
-pub fn get_discord_status(state: State<AppState>) -> Result<DiscordStatus, String> {
+pub fn get_runtime_config() -> RuntimeReport {
+    runtime().report()
+}
+
+#[tauri::command]
+pub fn get_rate_limit_status(state: State<AppState>) -> Vec<EndpointStatus> {
+    state.services.http.rate_limits()
+}
+
+#[tauri::command]
+pub fn get_discord_status(state: State<AppState>) -> Result<DiscordStatus, FlavortimeError> {
     let enabled = lock(&state.config)?.app_enabled;
     maybe_ensure_discord_client(&state.discord, enabled)?;
     let (connected, active) = poll_discord_status(&state.discord, enabled, false)?;
@@ -86,7 +162,7 @@ pub fn get_discord_status(state: State<AppState>) -> Result<DiscordStatus, Strin
 }
 
 #[tauri::command]
-pub fn force_refresh_discord(state: State<AppState>) -> Result<DiscordStatus, String> {
+pub fn force_refresh_discord(state: State<AppState>) -> Result<DiscordStatus, FlavortimeError> {
     let enabled = lock(&state.config)?.app_enabled;
     maybe_ensure_discord_client(&state.discord, enabled)?;
     let (connected, active) = poll_discord_status(&state.discord, enabled, true)?;
@@ -94,7 +170,7 @@ pub fn force_refresh_discord(state: State<AppState>) -> Result<DiscordStatus, St
 }
 
 #[tauri::command]
-pub fn get_status(app: AppHandle, state: State<AppState>) -> Result<Status, String> {
+pub fn get_status(app: AppHandle, state: State<AppState>) -> Result<Status, FlavortimeError> {
     let mut cfg = lock(&state.config)?;
     let mut changed = false;
 
@@ -131,6 +207,18 @@ pub fn get_status(app: AppHandle, state: State<AppState>) -> Result<Status, Stri
         show_time_tracking: cfg.show_time_tracking,
         launch_at_startup: cfg.launch_at_startup,
         app_enabled: cfg.app_enabled,
+        tracking_backend: tracking_backend_name(&cfg.tracking_backend).to_string(),
+        wakatime_base_url: cfg.wakatime_base_url.clone(),
+        wakatime_api_key_set: cfg.wakatime_api_key.is_some(),
+        max_snapshot_staleness_minutes: cfg.max_snapshot_staleness_minutes,
+        window_mode: cfg.window_mode.clone(),
+        presence_project_hours: cfg.presence_project_hours,
+        presence_template: cfg.presence_template.clone(),
+        presence_profiles: cfg.presence_profiles.clone(),
+        active_presence_profile: cfg.active_presence_profile.clone(),
+        privacy_rules: cfg.privacy_rules.clone(),
+        daily_goal_minutes: cfg.daily_goal_minutes,
+        history_days: cfg.history_days,
     })
 }
 
@@ -138,32 +226,59 @@ pub fn get_status(app: AppHandle, state: State<AppState>) -> Result<Status, Stri
 pub async fn login_with_flavortown_api_key(
     state: State<'_, AppState>,
     api_key: String,
-) -> Result<bool, String> {
+) -> Result<bool, FlavortimeError> {
+    login_with_flavortown_api_key_from_state(&state, &api_key).await
+}
+
+#[tauri::command]
+pub fn login_as_adult(state: State<AppState>) -> Result<(), FlavortimeError> {
+    login_as_adult_from_state(&state)
+}
+
+#[tauri::command]
+pub async fn logout(state: State<'_, AppState>) -> Result<(), FlavortimeError> {
+    logout_from_state(&state).await
+}
+
+pub(crate) async fn login_with_flavortown_api_key_from_state(
+    state: &AppState,
+    api_key: &str,
+) -> Result<bool, FlavortimeError> {
     let api_key = api_key.trim();
     if api_key.is_empty() {
-        return Err("API key is required".to_string());
+        return Err(FlavortimeError::InvalidInput(
+            "API key is required".to_string(),
+        ));
     }
 
-    let user = flavortown::current_user(api_key).await?;
+    let user = state.services.flavortown.current_user(api_key).await?;
     let slack_id = user.slack_id;
 
-    let window = rolling_24h_window();
-    let _ = Hackatime::user_projects_details(
-        &slack_id,
-        Some(window.start_rfc3339.as_str()),
-        Some(window.end_rfc3339.as_str()),
-    )
-    .await?;
+    let window = tracking_window(&lock(&state.config)?.window_mode);
+    let _ = state
+        .services
+        .hackatime
+        .user_projects_details(
+            &slack_id,
+            Some(window.start_rfc3339.as_str()),
+            Some(window.end_rfc3339.as_str()),
+        )
+        .await?;
 
-    let codes = fetch_codes(&slack_id).await.unwrap_or_else(|err| {
-        log::warn!("Failed to fetch referral codes after login: {err}");
-        Vec::new()
-    });
+    let codes = state
+        .services
+        .referrals
+        .fetch_codes(&slack_id)
+        .await
+        .unwrap_or_else(|err| {
+            log::warn!("Failed to fetch referral codes after login: {err}");
+            Vec::new()
+        });
 
     let should_reconnect = {
         let mut cfg = lock(&state.config)?;
         cfg.auth_mode = Mode::Hackatime;
-        cfg.flavortown_api_key = Some(api_key.to_string());
+        cfg.set_flavortown_api_key(Some(api_key.to_string()))?;
         cfg.slack_id = Some(slack_id);
         cfg.available_referral_codes = codes;
         cfg.show_time_tracking = true;
@@ -173,11 +288,11 @@ pub async fn login_with_flavortown_api_key(
     };
 
     *lock(&state.flavortime_session_id)? = None;
-    reset_sharing_session(&state)?;
-    ensure_flavortime_session_id(&state, api_key).await?;
+    reset_sharing_session(state)?;
+    ensure_flavortime_session_id(state, api_key).await?;
 
     if should_reconnect {
-        if let Err(err) = (|| -> Result<(), String> {
+        if let Err(err) = (|| -> Result<(), FlavortimeError> {
             ensure_discord_client(&state.discord)?;
             retry_discord_connection(&state.discord)?;
             let cfg = lock(&state.config)?;
@@ -187,15 +302,15 @@ pub async fn login_with_flavortown_api_key(
             log::warn!("Discord reconnection after login failed (non-fatal): {err}");
         }
     }
+    state.scheduler.wake();
     Ok(true)
 }
 
-#[tauri::command]
-pub fn login_as_adult(state: State<AppState>) -> Result<(), String> {
+pub(crate) fn login_as_adult_from_state(state: &AppState) -> Result<(), FlavortimeError> {
     let should_reconnect = {
         let mut cfg = lock(&state.config)?;
         cfg.auth_mode = Mode::Adult;
-        cfg.flavortown_api_key = None;
+        cfg.set_flavortown_api_key(None)?;
         cfg.slack_id = None;
         cfg.available_referral_codes.clear();
         cfg.selected_referral_code = None;
@@ -205,7 +320,8 @@ pub fn login_as_adult(state: State<AppState>) -> Result<(), String> {
     };
 
     *lock(&state.flavortime_session_id)? = None;
-    reset_sharing_session(&state)?;
+    reset_sharing_session(state)?;
+    state.snapshots.clear();
 
     if should_reconnect {
         ensure_discord_client(&state.discord)?;
@@ -216,16 +332,21 @@ pub fn login_as_adult(state: State<AppState>) -> Result<(), String> {
     sync_discord(&cfg, &state.discord)
 }
 
-#[tauri::command]
-pub async fn logout(state: State<'_, AppState>) -> Result<(), String> {
-    if let Err(err) = close_flavortime_session_from_state(&state, false).await {
+pub(crate) async fn logout_from_state(state: &AppState) -> Result<(), FlavortimeError> {
+    if let Err(err) = close_flavortime_session_from_state(state, false).await {
         log::warn!("Flavortime session close during logout failed (non-fatal): {err}");
     }
 
     {
         let mut cfg = lock(&state.config)?;
+        let custom_tracking = cfg.tracking_backend != TrackingBackend::Hackatime;
         cfg.reset();
         cfg.save()?;
+        if custom_tracking {
+            state
+                .services
+                .select_tracking(&cfg.tracking_backend, None, None)?;
+        }
     }
 
     let mut rpc = lock(&state.discord)?;
@@ -235,11 +356,13 @@ pub async fn logout(state: State<'_, AppState>) -> Result<(), String> {
     *rpc = None;
     *lock(&state.flavortime_session_id)? = None;
     *lock(&state.last_sharing_tick)? = None;
-    Ok(())
+    state.snapshots.clear();
+    clear_history(state)?;
+    clear_snapshot_cache(state)
 }
 
 #[tauri::command]
-pub async fn close_flavortime_session(state: State<'_, AppState>) -> Result<(), String> {
+pub async fn close_flavortime_session(state: State<'_, AppState>) -> Result<(), FlavortimeError> {
     close_flavortime_session_from_state(&state, true).await
 }
 
@@ -247,9 +370,10 @@ pub async fn close_flavortime_session(state: State<'_, AppState>) -> Result<(),
 pub fn set_selected_referral_code(
     state: State<AppState>,
     code: Option<String>,
-) -> Result<(), String> {
+) -> Result<(), FlavortimeError> {
     let mut cfg = lock(&state.config)?;
     cfg.selected_referral_code = trimmed(code);
+    cfg.capture_active_profile();
     cfg.save()?;
     sync_discord(&cfg, &state.discord)
 }
@@ -258,25 +382,28 @@ pub fn set_selected_referral_code(
 pub fn set_custom_referral_code(
     state: State<AppState>,
     code: Option<String>,
-) -> Result<(), String> {
+) -> Result<(), FlavortimeError> {
     let mut cfg = lock(&state.config)?;
     cfg.custom_referral_code = trimmed(code);
+    cfg.capture_active_profile();
     cfg.save()?;
     sync_discord(&cfg, &state.discord)
 }
 
 #[tauri::command]
-pub fn set_show_referral_code(state: State<AppState>, show: bool) -> Result<(), String> {
+pub fn set_show_referral_code(state: State<AppState>, show: bool) -> Result<(), FlavortimeError> {
     let mut cfg = lock(&state.config)?;
     cfg.show_referral_code = show;
+    cfg.capture_active_profile();
     cfg.save()?;
     sync_discord(&cfg, &state.discord)
 }
 
 #[tauri::command]
-pub fn set_show_time_tracking(state: State<AppState>, show: bool) -> Result<(), String> {
+pub fn set_show_time_tracking(state: State<AppState>, show: bool) -> Result<(), FlavortimeError> {
     let mut cfg = lock(&state.config)?;
     cfg.show_time_tracking = show;
+    cfg.capture_active_profile();
     cfg.save()
 }
 
@@ -285,12 +412,12 @@ pub fn set_launch_at_startup(
     app: AppHandle,
     state: State<AppState>,
     enabled: bool,
-) -> Result<(), String> {
+) -> Result<(), FlavortimeError> {
     let autolaunch = app.autolaunch();
     if enabled {
-        autolaunch.enable().map_err(stringify)?;
+        autolaunch.enable().map_err(system_error)?;
     } else {
-        autolaunch.disable().map_err(stringify)?;
+        autolaunch.disable().map_err(system_error)?;
     }
 
     let mut cfg = lock(&state.config)?;
@@ -299,7 +426,86 @@ pub fn set_launch_at_startup(
 }
 
 #[tauri::command]
-pub fn set_app_enabled(state: State<AppState>, enabled: bool) -> Result<(), String> {
+pub fn set_daily_goal(state: State<AppState>, minutes: u64) -> Result<(), FlavortimeError> {
+    set_daily_goal_from_state(&state, minutes)
+}
+
+#[tauri::command]
+pub fn set_history_days(state: State<AppState>, days: u32) -> Result<(), FlavortimeError> {
+    set_history_days_from_state(&state, days)
+}
+
+#[tauri::command]
+pub fn get_history(state: State<AppState>) -> Result<HistorySummary, FlavortimeError> {
+    history_summary(&state)
+}
+
+#[tauri::command]
+pub fn get_sessions(
+    state: State<AppState>,
+    start_date: String,
+    end_date: String,
+) -> Result<SessionReport, FlavortimeError> {
+    get_sessions_from_state(&state, &start_date, &end_date)
+}
+
+#[tauri::command]
+pub async fn get_stats(
+    state: State<'_, AppState>,
+    range: StatsRange,
+) -> Result<StatsReport, FlavortimeError> {
+    get_stats_from_state(&state, range).await
+}
+
+#[tauri::command]
+pub async fn export_history(
+    state: State<'_, AppState>,
+    start_date: String,
+    end_date: String,
+    format: ExportFormat,
+    path: String,
+    projects: Option<Vec<String>>,
+) -> Result<ExportSummary, FlavortimeError> {
+    export_history_from_state(
+        &state,
+        &start_date,
+        &end_date,
+        format,
+        &path,
+        &projects.unwrap_or_default(),
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn refresh_history(
+    state: State<'_, AppState>,
+) -> Result<HistorySummary, FlavortimeError> {
+    refresh_history_from_state(&state).await
+}
+
+#[tauri::command]
+pub fn set_max_snapshot_staleness(
+    state: State<AppState>,
+    minutes: u64,
+) -> Result<(), FlavortimeError> {
+    set_max_snapshot_staleness_from_state(&state, minutes)
+}
+
+#[tauri::command]
+pub fn set_window_mode(
+    state: State<AppState>,
+    mode: String,
+    utc_offset: Option<String>,
+    day_start_hour: Option<u32>,
+) -> Result<(), FlavortimeError> {
+    set_window_mode_from_state(&state, &mode, utc_offset, day_start_hour)?;
+    state.scheduler.wake();
+    Ok(())
+}
+
+#[tauri::command]
+pub fn set_app_enabled(state: State<AppState>, enabled: bool) -> Result<(), FlavortimeError> {
     {
         let mut cfg = lock(&state.config)?;
         cfg.app_enabled = enabled;
@@ -319,109 +525,46 @@ pub fn set_app_enabled(state: State<AppState>, enabled: bool) -> Result<(), Stri
 }
 
 #[tauri::command]
-pub async fn get_hackatime_data(state: State<'_, AppState>) -> Result<HackatimeData, String> {
-    let (auth_mode, slack_id, app_enabled, show_time_tracking, show_referral_code) = {
-        let cfg = lock(&state.config)?;
-        (
-            cfg.auth_mode.clone(),
-            required(cfg.slack_id.clone(), "Not logged in with Flavortime")?,
-            cfg.app_enabled,
-            cfg.show_time_tracking,
-            cfg.show_referral_code,
-        )
-    };
-
-    if !matches!(auth_mode, Mode::Hackatime) {
-        return Err("Not logged in with Flavortime".to_string());
+pub async fn get_hackatime_data(
+    state: State<'_, AppState>,
+) -> Result<HackatimeData, FlavortimeError> {
+    if let Some(data) = state.snapshots.latest() {
+        return Ok(data);
     }
-
-    let snapshot = fetch_hackatime_snapshot(&slack_id).await?;
-
-    let sharing_enabled = app_enabled && (show_time_tracking || show_referral_code);
-    let discord_connected = {
-        let mut rpc = lock(&state.discord)?;
-        if let Some(client) = rpc.as_mut() {
-            client.maybe_recover();
-            client.refresh_activity();
-            client.is_ready()
-        } else {
-            false
-        }
-    };
-
-    let should_accumulate = sharing_enabled && discord_connected && !snapshot.heartbeat_idle;
-    let sharing_active_seconds_total = accumulate_sharing_seconds(&state, should_accumulate)?;
-
-    Ok(HackatimeData {
-        current_project: snapshot.current_project,
-        total_hours: snapshot.total_hours,
-        heartbeat_idle: snapshot.heartbeat_idle,
-        sharing_active_seconds_total,
-    })
+    get_hackatime_data_from_state(&state).await
 }
 
 #[tauri::command]
-pub async fn send_flavortown_heartbeat(state: State<'_, AppState>) -> Result<u64, String> {
-    let (auth_mode, api_key, sharing_active_seconds_total) = {
-        let cfg = lock(&state.config)?;
-        (
-            cfg.auth_mode.clone(),
-            cfg.flavortown_api_key.clone(),
-            cfg.sharing_active_seconds_total,
-        )
-    };
-
-    if !matches!(auth_mode, Mode::Hackatime) {
-        return Ok(0);
-    }
-
-    let api_key = match api_key {
-        Some(value) if !value.trim().is_empty() => value,
-        _ => return Ok(0),
-    };
-
-    let session_id = ensure_flavortime_session_id(&state, &api_key).await?;
-    let metadata = flavortown::session_metadata();
+pub async fn send_flavortown_heartbeat(state: State<'_, AppState>) -> Result<u64, FlavortimeError> {
+    send_flavortown_heartbeat_from_state(&state).await
+}
 
-    match flavortown::send_heartbeat(
-        &api_key,
-        &session_id,
-        sharing_active_seconds_total,
-        metadata.platform,
-        metadata.app_version,
-    )
-    .await?
-    {
-        flavortown::HeartbeatOutcome::ActiveUsers(count) => Ok(count),
-        flavortown::HeartbeatOutcome::InvalidSessionId => {
-            let session_id = rotate_flavortime_session_id(&state, &api_key).await?;
-            let sharing_total_after_rotate = lock(&state.config)?.sharing_active_seconds_total;
+#[tauri::command]
+pub fn set_tracking_backend(
+    state: State<AppState>,
+    backend: String,
+    base_url: Option<String>,
+    api_key: Option<String>,
+) -> Result<(), FlavortimeError> {
+    set_tracking_backend_from_state(&state, &backend, base_url, api_key)?;
+    state.scheduler.wake();
+    Ok(())
+}
 
-            match flavortown::send_heartbeat(
-                &api_key,
-                &session_id,
-                sharing_total_after_rotate,
-                metadata.platform,
-                metadata.app_version,
-            )
-            .await?
-            {
-                flavortown::HeartbeatOutcome::ActiveUsers(count) => Ok(count),
-                flavortown::HeartbeatOutcome::InvalidSessionId => {
-                    Err("Flavortown rejected session ID after rotation".to_string())
-                }
-            }
-        }
-    }
+#[tauri::command]
+pub fn refresh_tracking(state: State<AppState>) {
+    state.scheduler.wake();
 }
 
 #[tauri::command]
-pub async fn refresh_referral_codes(state: State<'_, AppState>) -> Result<Vec<Referral>, String> {
+pub async fn refresh_referral_codes(
+    state: State<'_, AppState>,
+) -> Result<Vec<Referral>, FlavortimeError> {
     let slack_id = {
         let cfg = lock(&state.config)?;
-        required(cfg.slack_id.clone(), "No Slack ID available")?
+        required(cfg.slack_id.clone())?
     };
-    let codes = fetch_codes(&slack_id).await?;
+    let codes = state.services.referrals.fetch_codes(&slack_id).await?;
 
     let mut cfg = lock(&state.config)?;
     cfg.available_referral_codes = codes.clone();
@@ -431,7 +574,7 @@ pub async fn refresh_referral_codes(state: State<'_, AppState>) -> Result<Vec<Re
 }
 
 #[tauri::command]
-pub fn init_discord(state: State<AppState>) -> Result<(), String> {
+pub fn init_discord(state: State<AppState>) -> Result<(), FlavortimeError> {
     let (enabled, referral, show_referral_button) = {
         let cfg = lock(&state.config)?;
         (cfg.app_enabled, cfg.display_code(), cfg.show_referral_code)
@@ -443,7 +586,7 @@ pub fn init_discord(state: State<AppState>) -> Result<(), String> {
     if let Some(client) = rpc.as_mut() {
         client.maybe_recover();
         client.set_enabled(enabled);
-        client.update(None, None, referral, show_referral_button);
+        client.update(Activity::default(), referral, show_referral_button);
     }
     Ok(())
 }
@@ -452,41 +595,132 @@ pub fn init_discord(state: State<AppState>) -> Result<(), String> {
 pub fn update_discord_presence(
     state: State<AppState>,
     project: Option<String>,
+    language: Option<String>,
+    project_hours: Option<f64>,
     hours: Option<f64>,
-) -> Result<(), String> {
-    let (show_time, referral, show_referral_button, enabled) = {
-        let cfg = lock(&state.config)?;
-        (
-            cfg.show_time_tracking,
-            cfg.display_code(),
-            cfg.show_referral_code,
-            cfg.app_enabled,
-        )
+) -> Result<(), FlavortimeError> {
+    let public_name = match project {
+        Some(name) => privacy_rules(&*lock(&state.config)?).public_name(&name),
+        None => None,
+    };
+    let visible = public_name.is_some();
+    let activity = Activity {
+        project: public_name,
+        language: language.filter(|_| visible),
+        project_hours: project_hours.filter(|_| visible),
+        hours,
+        streak: None,
     };
-    let hours = if show_time { hours } else { None };
+    update_discord_presence_from_state(&state, activity)
+}
 
-    if enabled {
-        ensure_discord_client(&state.discord)?;
-    }
+#[tauri::command]
+pub fn set_presence_template(
+    state: State<AppState>,
+    template: Option<PresenceTemplate>,
+) -> Result<(), FlavortimeError> {
+    set_presence_template_from_state(&state, template)
+}
 
-    let mut rpc = lock(&state.discord)?;
-    if let Some(client) = rpc.as_mut() {
-        client.maybe_recover();
-        client.update(project, hours, referral, show_referral_button);
-    }
+#[tauri::command]
+pub fn create_presence_profile(
+    app: AppHandle,
+    state: State<AppState>,
+    name: String,
+) -> Result<PresenceProfiles, FlavortimeError> {
+    let profiles = update_presence_profiles_from_state(&state, |cfg| cfg.create_profile(&name))?;
+    emit_presence_profiles(&app, &profiles);
+    Ok(profiles)
+}
+
+#[tauri::command]
+pub fn rename_presence_profile(
+    app: AppHandle,
+    state: State<AppState>,
+    name: String,
+    new_name: String,
+) -> Result<PresenceProfiles, FlavortimeError> {
+    let profiles =
+        update_presence_profiles_from_state(&state, |cfg| cfg.rename_profile(&name, &new_name))?;
+    emit_presence_profiles(&app, &profiles);
+    Ok(profiles)
+}
+
+#[tauri::command]
+pub fn delete_presence_profile(
+    app: AppHandle,
+    state: State<AppState>,
+    name: String,
+) -> Result<PresenceProfiles, FlavortimeError> {
+    let profiles = update_presence_profiles_from_state(&state, |cfg| cfg.delete_profile(&name))?;
+    emit_presence_profiles(&app, &profiles);
+    Ok(profiles)
+}
+
+#[tauri::command]
+pub fn activate_presence_profile(
+    app: AppHandle,
+    state: State<AppState>,
+    name: Option<String>,
+) -> Result<PresenceProfiles, FlavortimeError> {
+    let profiles = activate_presence_profile_from_state(&state, name.as_deref())?;
+    emit_presence_profiles(&app, &profiles);
+    Ok(profiles)
+}
+
+#[tauri::command]
+pub fn set_privacy_rules(
+    state: State<AppState>,
+    rules: Vec<PrivacyRule>,
+) -> Result<(), FlavortimeError> {
+    set_privacy_rules_from_state(&state, rules)
+}
+
+#[tauri::command]
+pub fn preview_presence(
+    state: State<AppState>,
+    template: Option<PresenceTemplate>,
+) -> Result<PresencePreview, FlavortimeError> {
+    preview_presence_from_state(&state, template)
+}
+
+#[tauri::command]
+pub fn set_presence_project_hours(
+    state: State<AppState>,
+    source: String,
+) -> Result<(), FlavortimeError> {
+    let source = match source.as_str() {
+        "project" => ProjectHoursSource::Project,
+        "total" => ProjectHoursSource::Total,
+        other => {
+            return Err(FlavortimeError::InvalidInput(format!(
+                "Unknown project hours source: {other}"
+            )))
+        }
+    };
+
+    let mut cfg = lock(&state.config)?;
+    cfg.presence_project_hours = source;
+    cfg.save()?;
+    drop(cfg);
+    state.scheduler.wake();
     Ok(())
 }
 
 #[tauri::command]
-pub fn set_adult_referral_code(state: State<AppState>, code: String) -> Result<(), String> {
+pub fn set_adult_referral_code(
+    state: State<AppState>,
+    code: String,
+) -> Result<(), FlavortimeError> {
     let mut cfg = lock(&state.config)?;
     cfg.custom_referral_code = trimmed(Some(code));
+    cfg.capture_active_profile();
     cfg.save()?;
     sync_discord(&cfg, &state.discord)
 }
 
 #[tauri::command]
-pub async fn check_for_update(app: AppHandle) -> Result<UpdaterStatus, String> {
+pub async fn check_for_update(app: AppHandle) -> Result<UpdaterStatus, FlavortimeError> {
     let current_version = app.package_info().version.to_string();
     let dev_mode = cfg!(debug_assertions);
 
@@ -539,33 +773,33 @@ pub async fn check_for_update(app: AppHandle) -> Result<UpdaterStatus, String> {
 }
 
 #[tauri::command]
-pub async fn download_update(app: AppHandle) -> Result<(), String> {
+pub async fn download_update(app: AppHandle) -> Result<(), FlavortimeError> {
     #[cfg(target_os = "linux")]
     {
-        return Err(
+        return Err(FlavortimeError::Updater(
             "Automatic updater is disabled on Linux. Please redownload from https://github.com/hackclub/flavortime"
                 .to_string(),
-        );
+        ));
     }
 
     #[cfg(target_os = "macos")]
     {
-        let current_exe = std::env::current_exe().map_err(stringify)?;
+        let current_exe = std::env::current_exe().map_err(system_error)?;
         let exe_path = current_exe.to_string_lossy();
         if exe_path.contains("/AppTranslocation/") || exe_path.starts_with("/Volumes/") {
-            return Err(
+            return Err(FlavortimeError::Updater(
                 "Install Flavortime to /Applications and open it from there before updating."
                     .to_string(),
-            );
+            ));
         }
     }
 
-    let updater = app.updater().map_err(stringify)?;
+    let updater = app.updater().map_err(updater_error)?;
     let update = updater
         .check()
         .await
-        .map_err(stringify)?
-        .ok_or_else(|| "No update is currently available".to_string())?;
+        .map_err(updater_error)?
+        .ok_or_else(|| FlavortimeError::Updater("No update is currently available".to_string()))?;
 
     let progress_app = app.clone();
     let finish_app = app.clone();
@@ -576,7 +810,8 @@ pub async fn download_update(app: AppHandle) -> Result<(), String> {
             move |chunk_len, content_len| {
                 downloaded_bytes = downloaded_bytes.saturating_add(chunk_len as u64);
                 if let Some(total) = content_len.filter(|value| *value > 0) {
-                    let percent = ((downloaded_bytes as f64 / total as f64) * 100.0).clamp(0.0, 100.0);
+                    let percent =
+                        ((downloaded_bytes as f64 / total as f64) * 100.0).clamp(0.0, 100.0);
                     let _ = progress_app.emit("updater-download-progress", percent);
                 }
             },
@@ -585,111 +820,1036 @@ pub async fn download_update(app: AppHandle) -> Result<(), String> {
             },
         )
         .await
-        .map_err(stringify)
+        .map_err(updater_error)
 }
 
 #[tauri::command]
-pub fn restart_for_update(app: AppHandle) -> Result<(), String> {
+pub fn restart_for_update(app: AppHandle) -> Result<(), FlavortimeError> {
     app.request_restart();
     Ok(())
 }
 
-pub async fn close_flavortime_session_for_shutdown(app: &AppHandle) -> Result<(), String> {
-    let Some(state) = app.try_state::<AppState>() else {
-        return Ok(());
+pub(crate) fn set_tracking_backend_from_state(
+    state: &AppState,
+    backend: &str,
+    base_url: Option<String>,
+    api_key: Option<String>,
+) -> Result<(), FlavortimeError> {
+    let backend = match backend {
+        "hackatime" => TrackingBackend::Hackatime,
+        "wakatime" => TrackingBackend::WakaTime,
+        other => {
+            return Err(FlavortimeError::InvalidInput(format!(
+                "Unknown tracking backend: {other}"
+            )))
+        }
     };
 
-    close_flavortime_session_from_state(&state, false).await
-}
-
-fn accumulate_sharing_seconds(state: &AppState, session_active: bool) -> Result<u64, String> {
-    let now = unix_now_secs();
-    let mut last_tick = lock(&state.last_sharing_tick)?;
     let mut cfg = lock(&state.config)?;
-
-    if let Some(previous) = *last_tick {
-        let elapsed = now.saturating_sub(previous).min(120);
-        if elapsed > 0 && session_active {
-            cfg.sharing_active_seconds_total =
-                cfg.sharing_active_seconds_total.saturating_add(elapsed);
-            cfg.save()?;
+    let base_url = trimmed(base_url)
+        .map(|url| url.trim_end_matches('/').to_string())
+        .or_else(|| cfg.wakatime_base_url.clone());
+    let api_key = trimmed(api_key).or_else(|| cfg.wakatime_api_key.clone());
+
+    if backend == TrackingBackend::WakaTime {
+        let url = base_url.as_deref().unwrap_or_default();
+        if !(url.starts_with("https://") || url.starts_with("http://")) {
+            return Err(FlavortimeError::InvalidInput(
+                "WakaTime server URL must start with http:// or https://".to_string(),
+            ));
         }
     }
 
-    *last_tick = Some(now);
-    Ok(cfg.sharing_active_seconds_total)
-}
-
-async fn fetch_hackatime_snapshot(slack_id: &str) -> Result<HackatimeSnapshot, String> {
-    let window = rolling_24h_window();
-    let projects = Hackatime::user_projects_details(
-        slack_id,
-        Some(window.start_rfc3339.as_str()),
-        Some(window.end_rfc3339.as_str()),
-    )
-    .await?;
+    state
+        .services
+        .select_tracking(&backend, base_url.as_deref(), api_key.as_deref())?;
 
-    let total_seconds = projects
-        .iter()
-        .map(|project| project.total_seconds.max(0.0))
-        .sum::<f64>();
-    let total_hours = total_seconds / 3600.0;
+    if api_key != cfg.wakatime_api_key {
+        cfg.set_wakatime_api_key(api_key)?;
+    }
+    cfg.tracking_backend = backend;
+    cfg.wakatime_base_url = base_url;
+    cfg.save()?;
+    drop(cfg);
+    state.snapshots.clear();
+    clear_snapshot_cache(state)
+}
 
-    let latest = latest_project(&projects, Some(window.start_unix));
-    let heartbeat_idle = latest
-        .as_ref()
-        .map(|(_, unix_time)| unix_now_secs().saturating_sub(*unix_time) > 180)
-        .unwrap_or(true);
+pub(crate) fn set_window_mode_from_state(
+    state: &AppState,
+    mode: &str,
+    utc_offset: Option<String>,
+    day_start_hour: Option<u32>,
+) -> Result<(), FlavortimeError> {
+    let window_mode = match mode {
+        "rolling_24h" => WindowMode::Rolling24h,
+        "local_day" => WindowMode::LocalDay,
+        "custom" => {
+            let utc_offset_minutes = match trimmed(utc_offset) {
+                Some(offset) => parse_utc_offset(&offset)?,
+                None => 0,
+            };
+            let day_start_hour = day_start_hour.unwrap_or(0);
+            if day_start_hour > 23 {
+                return Err(FlavortimeError::InvalidInput(
+                    "Day start hour must be between 0 and 23".to_string(),
+                ));
+            }
+            WindowMode::Custom {
+                utc_offset_minutes,
+                day_start_hour,
+            }
+        }
+        other => {
+            return Err(FlavortimeError::InvalidInput(format!(
+                "Unknown window mode: {other}"
+            )))
+        }
+    };
 
-    let current_project = latest.map(|(name, _)| Project {
-        name: name.to_string(),
-        hours: total_hours,
-    });
+    {
+        let mut cfg = lock(&state.config)?;
+        if cfg.window_mode == window_mode {
+            return Ok(());
+        }
+        cfg.window_mode = window_mode;
+        cfg.save()?;
+    }
 
-    Ok(HackatimeSnapshot {
-        current_project,
-        total_hours,
-        heartbeat_idle,
-    })
+    state.snapshots.clear();
+    clear_snapshot_cache(state)
 }
 
-async fn ensure_flavortime_session_id(state: &AppState, api_key: &str) -> Result<String, String> {
-    if let Some(existing) = lock(&state.flavortime_session_id)?.clone() {
-        return Ok(existing);
+pub(crate) fn set_max_snapshot_staleness_from_state(
+    state: &AppState,
+    minutes: u64,
+) -> Result<(), FlavortimeError> {
+    if minutes > MAX_SNAPSHOT_STALENESS_MINUTES {
+        return Err(FlavortimeError::InvalidInput(format!(
+            "Max staleness must be at most {MAX_SNAPSHOT_STALENESS_MINUTES} minutes"
+        )));
     }
 
-    rotate_flavortime_session_id(state, api_key).await
+    let mut cfg = lock(&state.config)?;
+    cfg.max_snapshot_staleness_minutes = minutes;
+    cfg.save()
 }
 
-async fn rotate_flavortime_session_id(state: &AppState, api_key: &str) -> Result<String, String> {
-    let metadata = flavortown::session_metadata();
-    let session_id =
-        flavortown::create_session(api_key, metadata.platform, metadata.app_version).await?;
-    *lock(&state.flavortime_session_id)? = Some(session_id.clone());
-    reset_sharing_session(state)?;
+pub(crate) fn set_daily_goal_from_state(
+    state: &AppState,
+    minutes: u64,
+) -> Result<(), FlavortimeError> {
+    if minutes > MAX_DAILY_GOAL_MINUTES {
+        return Err(FlavortimeError::InvalidInput(format!(
+            "Daily goal must be at most {MAX_DAILY_GOAL_MINUTES} minutes"
+        )));
+    }
+
+    {
+        let mut cfg = lock(&state.config)?;
+        cfg.daily_goal_minutes = minutes;
+        cfg.save()?;
+    }
+
+    state.scheduler.wake();
+    Ok(())
+}
+
+pub(crate) fn set_history_days_from_state(
+    state: &AppState,
+    days: u32,
+) -> Result<(), FlavortimeError> {
+    if !(1..=MAX_HISTORY_DAYS).contains(&days) {
+        return Err(FlavortimeError::InvalidInput(format!(
+            "History must cover between 1 and {MAX_HISTORY_DAYS} days"
+        )));
+    }
+
+    let mut cfg = lock(&state.config)?;
+    cfg.history_days = days;
+    cfg.save()
+}
+
+pub(crate) fn history_summary(state: &AppState) -> Result<HistorySummary, FlavortimeError> {
+    let (daily_goal_minutes, window_mode) = {
+        let cfg = lock(&state.config)?;
+        (cfg.daily_goal_minutes, cfg.window_mode.clone())
+    };
+    let today = day_windows(&window_mode, 1)
+        .pop()
+        .map(|window| window.date)
+        .unwrap_or_default();
+
+    let history = lock(&state.history)?;
+    let (days, streaks, fetched_at) = match history.as_ref() {
+        Some(history) => (
+            history.days.clone(),
+            history.streaks(daily_goal_minutes as f64 * 60.0, &today),
+            history.fetched_at,
+        ),
+        None => (Vec::new(), Streaks::default(), 0),
+    };
+    Ok(HistorySummary {
+        days,
+        streaks,
+        daily_goal_minutes,
+        fetched_at,
+    })
+}
+
+pub(crate) fn history_refresh_due(state: &AppState) -> bool {
+    let Ok(window_mode) = lock(&state.config).map(|cfg| cfg.window_mode.clone()) else {
+        return false;
+    };
+    let today = day_windows(&window_mode, 1).pop().map(|window| window.date);
+    let Ok(history) = lock(&state.history) else {
+        return false;
+    };
+    history.as_ref().is_none_or(|history| {
+        unix_now_secs().saturating_sub(history.fetched_at) >= HISTORY_REFRESH_SECS
+            || today.is_some_and(|today| !history.has(&today))
+    })
+}
+
+pub(crate) async fn refresh_history_from_state(
+    state: &AppState,
+) -> Result<HistorySummary, FlavortimeError> {
+    let (auth_mode, slack_id, window_mode, days, day_key) = {
+        let cfg = lock(&state.config)?;
+        (
+            cfg.auth_mode.clone(),
+            required(cfg.slack_id.clone())?,
+            cfg.window_mode.clone(),
+            cfg.history_days.clamp(1, MAX_HISTORY_DAYS),
+            history_key(&cfg),
+        )
+    };
+
+    if !matches!(auth_mode, Mode::Hackatime) {
+        return Err(FlavortimeError::NotSignedIn);
+    }
+
+    let windows = day_windows(&window_mode, days);
+    let today = windows.last().map(|window| window.date.clone());
+    let mut history = lock(&state.history)?
+        .clone()
+        .filter(|history| history.matches(&slack_id, &day_key))
+        .unwrap_or_else(|| History::new(&slack_id, &day_key));
+
+    let mut result = Ok(());
+    for window in &windows {
+        if Some(&window.date) != today.as_ref() && history.has(&window.date) {
+            continue;
+        }
+        let projects = state
+            .services
+            .hackatime
+            .user_projects_details(
+                &slack_id,
+                Some(window.start_rfc3339.as_str()),
+                Some(window.end_rfc3339.as_str()),
+            )
+            .await;
+        match projects {
+            Ok(projects) => history.record(
+                &window.date,
+                projects
+                    .iter()
+                    .map(|project| project.total_seconds.max(0.0))
+                    .sum(),
+            ),
+            Err(err) => {
+                result = Err(err);
+                break;
+            }
+        }
+    }
+
+    history.retain_last(days as usize);
+    if result.is_ok() {
+        history.fetched_at = unix_now_secs();
+    }
+    if let Err(err) = history.save() {
+        log::warn!("Failed to save history: {err}");
+    }
+    *lock(&state.history)? = Some(history);
+
+    result?;
+    history_summary(state)
+}
+
+fn current_streak(state: &AppState) -> Option<u32> {
+    history_summary(state)
+        .ok()
+        .map(|summary| summary.streaks.current)
+}
+
+fn history_key(cfg: &Config) -> String {
+    let backend = match cfg.tracking_backend {
+        TrackingBackend::Hackatime => "hackatime".to_string(),
+        TrackingBackend::WakaTime => format!(
+            "wakatime:{}",
+            cfg.wakatime_base_url.as_deref().unwrap_or_default()
+        ),
+    };
+    match cfg.window_mode {
+        WindowMode::Custom {
+            utc_offset_minutes,
+            day_start_hour,
+        } => format!("{backend}|{utc_offset_minutes:+}@{day_start_hour}"),
+        WindowMode::Rolling24h | WindowMode::LocalDay => format!("{backend}|local"),
+    }
+}
+
+pub(crate) async fn get_stats_from_state(
+    state: &AppState,
+    range: StatsRange,
+) -> Result<StatsReport, FlavortimeError> {
+    let (auth_mode, slack_id, window_mode, day_key) = {
+        let cfg = lock(&state.config)?;
+        (
+            cfg.auth_mode.clone(),
+            required(cfg.slack_id.clone())?,
+            cfg.window_mode.clone(),
+            history_key(&cfg),
+        )
+    };
+
+    if !matches!(auth_mode, Mode::Hackatime) {
+        return Err(FlavortimeError::NotSignedIn);
+    }
+
+    let windows = day_windows(&window_mode, range.days());
+    let today = windows.last().map(|window| window.date.clone());
+    let days = load_day_projects(state, &slack_id, &day_key, &windows, today.as_deref()).await?;
+    Ok(StatsReport::aggregate(range, &days))
+}
+
+pub(crate) async fn export_history_from_state(
+    state: &AppState,
+    start_date: &str,
+    end_date: &str,
+    format: ExportFormat,
+    path: &str,
+    projects: &[String],
+) -> Result<ExportSummary, FlavortimeError> {
+    let path = export::export_path(path, format)?;
+    let (start, end) = (parse_date(start_date)?, parse_date(end_date)?);
+    if end < start {
+        return Err(FlavortimeError::InvalidInput(
+            "End date must not be before start date".to_string(),
+        ));
+    }
+
+    let (auth_mode, slack_id, window_mode, day_key) = {
+        let cfg = lock(&state.config)?;
+        (
+            cfg.auth_mode.clone(),
+            required(cfg.slack_id.clone())?,
+            cfg.window_mode.clone(),
+            history_key(&cfg),
+        )
+    };
+
+    if !matches!(auth_mode, Mode::Hackatime) {
+        return Err(FlavortimeError::NotSignedIn);
+    }
+
+    let today = day_windows(&window_mode, 1)
+        .pop()
+        .map(|window| window.date)
+        .unwrap_or_default();
+    let days_back = parse_date(&today)?.signed_duration_since(start).num_days() + 1;
+    if days_back < 1 {
+        return Err(FlavortimeError::InvalidInput(
+            "Start date is in the future".to_string(),
+        ));
+    }
+    if days_back > MAX_EXPORT_DAYS {
+        return Err(FlavortimeError::InvalidInput(format!(
+            "Exports can reach back at most {MAX_EXPORT_DAYS} days"
+        )));
+    }
+
+    let end_date = end.format("%Y-%m-%d").to_string().min(today.clone());
+    let windows: Vec<DayWindow> = day_windows(&window_mode, days_back as u32)
+        .into_iter()
+        .filter(|window| window.date <= end_date)
+        .collect();
+    let days = load_day_projects(state, &slack_id, &day_key, &windows, Some(&today)).await?;
+    let days = export::filter_projects(days, projects);
+    let blocks = match (format, windows.first(), windows.last()) {
+        (ExportFormat::Ics, Some(first), Some(last)) => state
+            .services
+            .hackatime
+            .coding_blocks(&first.start_rfc3339, &last.end_rfc3339)
+            .await?
+            .map(|blocks| export::filter_blocks(blocks, projects)),
+        _ => None,
+    };
+
+    let start_date = start.format("%Y-%m-%d").to_string();
+    let contents = format.render(&start_date, &end_date, &days, blocks.as_deref())?;
+    export::write(&path, &contents)?;
+
+    Ok(ExportSummary {
+        path: path.display().to_string(),
+        entries: days.iter().map(|day| day.projects.len()).sum(),
+        days: days.len(),
+    })
+}
+
+async fn load_day_projects(
+    state: &AppState,
+    slack_id: &str,
+    day_key: &str,
+    windows: &[DayWindow],
+    today: Option<&str>,
+) -> Result<Vec<DayProjects>, FlavortimeError> {
+    let mut cache = lock(&state.stats)?
+        .clone()
+        .filter(|cache| cache.matches(slack_id, day_key))
+        .unwrap_or_else(|| StatsCache::new(slack_id, day_key));
+
+    let mut days = Vec::with_capacity(windows.len());
+    let mut result = Ok(());
+    for window in windows {
+        let closed = Some(window.date.as_str()) != today;
+        if let Some(cached) = cache.get(&window.date).filter(|_| closed) {
+            days.push(cached.clone());
+            continue;
+        }
+        let projects = state
+            .services
+            .hackatime
+            .user_projects_details(
+                slack_id,
+                Some(window.start_rfc3339.as_str()),
+                Some(window.end_rfc3339.as_str()),
+            )
+            .await;
+        match projects {
+            Ok(projects) => {
+                let day = day_projects(
+                    &window.date,
+                    projects
+                        .into_iter()
+                        .map(|project| (project.name, project.total_seconds)),
+                );
+                if closed {
+                    cache.record(day.clone());
+                }
+                days.push(day);
+            }
+            Err(err) => {
+                result = Err(err);
+                break;
+            }
+        }
+    }
+
+    cache.retain_last(STATS_CACHE_DAYS);
+    if let Err(err) = cache.save() {
+        log::warn!("Failed to save stats cache: {err}");
+    }
+    *lock(&state.stats)? = Some(cache);
+
+    result.map(|()| days)
+}
+
+pub(crate) fn get_sessions_from_state(
+    state: &AppState,
+    start_date: &str,
+    end_date: &str,
+) -> Result<SessionReport, FlavortimeError> {
+    let (from, until) = sessions::date_range(start_date, end_date)?;
+    let mut sessions = sessions::query(from, until);
+
+    if let Some(active) = lock(&state.session_log)?.as_ref() {
+        let now = unix_now_secs();
+        if let Some(summary) = sessions
+            .iter_mut()
+            .find(|summary| summary.id == active.id())
+        {
+            summary.counters = active.counters();
+            summary.last_seen_at = summary.last_seen_at.max(now);
+        }
+    }
+
+    Ok(SessionReport {
+        sharing_seconds: sessions
+            .iter()
+            .map(|session| session.counters.sharing_seconds)
+            .sum(),
+        discord_seconds: sessions
+            .iter()
+            .map(|session| session.counters.discord_seconds)
+            .sum(),
+        sessions,
+    })
+}
+
+fn restart_session_log(state: &AppState) -> Result<(), FlavortimeError> {
+    let now = unix_now_secs();
+    let mut log = lock(&state.session_log)?;
+    if let Some(previous) = log.take() {
+        previous.end(now);
+    }
+    *log = Some(ActiveSession::start(now));
+    Ok(())
+}
+
+fn end_session_log(state: &AppState) -> Result<(), FlavortimeError> {
+    if let Some(session) = lock(&state.session_log)?.take() {
+        session.end(unix_now_secs());
+    }
+    Ok(())
+}
+
+fn with_session_log(
+    state: &AppState,
+    update: impl FnOnce(&mut ActiveSession),
+) -> Result<(), FlavortimeError> {
+    if let Some(session) = lock(&state.session_log)?.as_mut() {
+        update(session);
+    }
+    Ok(())
+}
+
+fn clear_history(state: &AppState) -> Result<(), FlavortimeError> {
+    *lock(&state.history)? = None;
+    History::clear();
+    *lock(&state.stats)? = None;
+    StatsCache::clear();
+    Ok(())
+}
+
+pub(crate) async fn get_hackatime_data_from_state(
+    state: &AppState,
+) -> Result<HackatimeData, FlavortimeError> {
+    let result = fetch_hackatime_data(state).await;
+    state.snapshots.publish(result.clone());
+    result
+}
+
+async fn fetch_hackatime_data(state: &AppState) -> Result<HackatimeData, FlavortimeError> {
+    let (auth_mode, slack_id, sharing_enabled, max_staleness, window_mode, privacy) = {
+        let cfg = lock(&state.config)?;
+        (
+            cfg.auth_mode.clone(),
+            required(cfg.slack_id.clone())?,
+            cfg.app_enabled && (cfg.show_time_tracking || cfg.show_referral_code),
+            cfg.max_snapshot_staleness_minutes.saturating_mul(60),
+            cfg.window_mode.clone(),
+            privacy_rules(&cfg),
+        )
+    };
+
+    if !matches!(auth_mode, Mode::Hackatime) {
+        return Err(FlavortimeError::NotSignedIn);
+    }
+
+    let snapshot = load_hackatime_snapshot(state, &slack_id, &window_mode, max_staleness).await?;
+
+    let discord_connected = {
+        let mut rpc = lock(&state.discord)?;
+        if let Some(client) = rpc.as_mut() {
+            client.maybe_recover();
+            client.refresh_activity();
+            client.is_ready()
+        } else {
+            false
+        }
+    };
+
+    let should_accumulate =
+        sharing_enabled && discord_connected && !snapshot.heartbeat_idle && !snapshot.stale;
+    let sharing_active_seconds_total = accumulate_sharing_seconds(state, should_accumulate)?;
+
+    let now = unix_now_secs();
+    with_session_log(state, |session| {
+        let project = snapshot.current_project.as_ref();
+        session.observe_discord(discord_connected, now);
+        session.observe_project(
+            project.map(|project| project.name.as_str()),
+            snapshot.total_hours,
+            now,
+        );
+        session.flush_if_due(now);
+    })?;
+
+    let data = HackatimeData {
+        current_project: snapshot.current_project,
+        total_hours: snapshot.total_hours,
+        heartbeat_idle: snapshot.heartbeat_idle,
+        sharing_active_seconds_total,
+        stale: snapshot.stale,
+        fetched_at: snapshot.fetched_at,
+        window_mode,
+    };
+    Ok(apply_privacy(data, &privacy))
+}
+
+fn privacy_rules(cfg: &Config) -> PrivacyRules {
+    PrivacyRules::compile(&cfg.privacy_rules).unwrap_or_else(|err| {
+        log::warn!("Invalid privacy rules, hiding project names: {err}");
+        PrivacyRules::redact_all()
+    })
+}
+
+fn apply_privacy(mut data: HackatimeData, privacy: &PrivacyRules) -> HackatimeData {
+    if let Some(project) = data.current_project.as_mut() {
+        project.public_name = privacy.public_name(&project.name);
+    }
+    data
+}
+
+pub(crate) fn set_privacy_rules_from_state(
+    state: &AppState,
+    rules: Vec<PrivacyRule>,
+) -> Result<(), FlavortimeError> {
+    let privacy = PrivacyRules::compile(&rules)?;
+    {
+        let mut cfg = lock(&state.config)?;
+        cfg.privacy_rules = rules;
+        cfg.save()?;
+    }
+
+    if let Some(data) = state.snapshots.latest() {
+        state.snapshots.publish(Ok(apply_privacy(data, &privacy)));
+    }
+    Ok(())
+}
+
+pub(crate) async fn send_flavortown_heartbeat_from_state(
+    state: &AppState,
+) -> Result<u64, FlavortimeError> {
+    let result = heartbeat_flavortime_session(state).await;
+    if result.is_ok() {
+        touch_flavortime_session(state)?;
+    }
+    let outcome = result.as_ref().map(|_| ()).map_err(ToString::to_string);
+    with_session_log(state, |session| {
+        session.record_heartbeat(outcome, unix_now_secs())
+    })?;
+    result
+}
+
+async fn heartbeat_flavortime_session(state: &AppState) -> Result<u64, FlavortimeError> {
+    let (auth_mode, api_key, sharing_active_seconds_total) = {
+        let cfg = lock(&state.config)?;
+        (
+            cfg.auth_mode.clone(),
+            cfg.flavortown_api_key.clone(),
+            cfg.sharing_active_seconds_total,
+        )
+    };
+
+    if !matches!(auth_mode, Mode::Hackatime) {
+        return Ok(0);
+    }
+
+    let api_key = match api_key {
+        Some(value) if !value.trim().is_empty() => value,
+        _ => return Ok(0),
+    };
+
+    let session_id = ensure_flavortime_session_id(state, &api_key).await?;
+    let metadata = flavortown::session_metadata();
+
+    match state
+        .services
+        .flavortown
+        .send_heartbeat(
+            &api_key,
+            &session_id,
+            sharing_active_seconds_total,
+            &metadata,
+        )
+        .await?
+    {
+        flavortown::HeartbeatOutcome::ActiveUsers(count) => Ok(count),
+        flavortown::HeartbeatOutcome::InvalidSessionId => {
+            let session_id = rotate_flavortime_session_id(state, &api_key).await?;
+            let sharing_total_after_rotate = lock(&state.config)?.sharing_active_seconds_total;
+
+            match state
+                .services
+                .flavortown
+                .send_heartbeat(&api_key, &session_id, sharing_total_after_rotate, &metadata)
+                .await?
+            {
+                flavortown::HeartbeatOutcome::ActiveUsers(count) => Ok(count),
+                flavortown::HeartbeatOutcome::InvalidSessionId => {
+                    Err(FlavortimeError::InvalidSession)
+                }
+            }
+        }
+    }
+}
+
+pub(crate) fn activity_from_data(data: &HackatimeData) -> Activity {
+    let project = data
+        .current_project
+        .as_ref()
+        .filter(|project| project.public_name.is_some());
+    Activity {
+        project: project.and_then(|project| project.public_name.clone()),
+        language: project.and_then(|project| project.language.clone()),
+        project_hours: project.map(|project| project.hours),
+        hours: Some(data.total_hours),
+        streak: None,
+    }
+}
+
+fn presence_options(cfg: &Config) -> PresenceOptions {
+    PresenceOptions {
+        rolling_window: cfg.window_mode == WindowMode::Rolling24h,
+        project_hours: cfg.presence_project_hours,
+        template: cfg.presence_template.clone(),
+        daily_goal_minutes: cfg.daily_goal_minutes,
+    }
+}
+
+fn visible_activity(cfg: &Config, mut activity: Activity) -> Activity {
+    if !cfg.show_time_tracking {
+        activity.project_hours = None;
+        activity.hours = None;
+    }
+    activity
+}
+
+pub(crate) fn update_discord_presence_from_state(
+    state: &AppState,
+    mut activity: Activity,
+) -> Result<(), FlavortimeError> {
+    activity.streak = current_streak(state);
+    let (activity, referral, show_referral_button, enabled, options) = {
+        let cfg = lock(&state.config)?;
+        (
+            visible_activity(&cfg, activity),
+            cfg.display_code(),
+            cfg.show_referral_code,
+            cfg.app_enabled,
+            presence_options(&cfg),
+        )
+    };
+
+    if enabled {
+        ensure_discord_client(&state.discord)?;
+    }
+
+    let mut rpc = lock(&state.discord)?;
+    if let Some(client) = rpc.as_mut() {
+        client.maybe_recover();
+        client.set_options(options);
+        client.update(activity, referral, show_referral_button);
+    }
+    Ok(())
+}
+
+pub(crate) fn set_presence_template_from_state(
+    state: &AppState,
+    template: Option<PresenceTemplate>,
+) -> Result<(), FlavortimeError> {
+    let template = template.unwrap_or_default();
+    presence::validate(&template)?;
+
+    {
+        let mut cfg = lock(&state.config)?;
+        cfg.presence_template = template;
+        cfg.capture_active_profile();
+        cfg.save()?;
+    }
+
+    state.scheduler.wake();
+    Ok(())
+}
+
+pub(crate) fn activate_presence_profile_from_state(
+    state: &AppState,
+    name: Option<&str>,
+) -> Result<PresenceProfiles, FlavortimeError> {
+    update_presence_profiles_from_state(state, |cfg| match name {
+        Some(name) => cfg.activate_profile(name),
+        None => {
+            cfg.active_presence_profile = None;
+            Ok(())
+        }
+    })
+}
+
+pub(crate) fn update_presence_profiles_from_state(
+    state: &AppState,
+    change: impl FnOnce(&mut Config) -> Result<(), FlavortimeError>,
+) -> Result<PresenceProfiles, FlavortimeError> {
+    let profiles = {
+        let mut cfg = lock(&state.config)?;
+        change(&mut cfg)?;
+        cfg.save()?;
+        sync_discord(&cfg, &state.discord)?;
+        presence_profiles(&cfg)
+    };
+
+    state.scheduler.wake();
+    Ok(profiles)
+}
+
+pub(crate) fn presence_profiles(cfg: &Config) -> PresenceProfiles {
+    PresenceProfiles {
+        profiles: cfg
+            .presence_profiles
+            .iter()
+            .map(|profile| profile.name.clone())
+            .collect(),
+        active: cfg.active_presence_profile.clone(),
+    }
+}
+
+pub(crate) fn emit_presence_profiles(app: &AppHandle, profiles: &PresenceProfiles) {
+    let _ = app.emit(PRESENCE_PROFILES_EVENT, profiles.clone());
+}
+
+pub(crate) fn preview_presence_from_state(
+    state: &AppState,
+    template: Option<PresenceTemplate>,
+) -> Result<PresencePreview, FlavortimeError> {
+    let activity = state
+        .snapshots
+        .latest()
+        .map(|data| activity_from_data(&data))
+        .unwrap_or_default();
+    let activity = Activity {
+        streak: current_streak(state),
+        ..activity
+    };
+    let session_length = lock(&state.discord)?
+        .as_ref()
+        .and_then(DiscordPresenceManager::session_length);
+
+    let cfg = lock(&state.config)?;
+    let mut options = presence_options(&cfg);
+    if let Some(template) = template {
+        presence::validate(&template)?;
+        options.template = template;
+    }
+    let referral = cfg.display_code();
+    let presence = render_presence(
+        &options,
+        &visible_activity(&cfg, activity),
+        referral.clone(),
+        session_length,
+    );
+    let button_url = (cfg.show_referral_code && presence.button_label.is_some())
+        .then(|| presence::referral_url(referral.as_deref()));
+    Ok(PresencePreview {
+        presence,
+        button_url,
+    })
+}
+
+pub async fn close_flavortime_session_for_shutdown(app: &AppHandle) -> Result<(), FlavortimeError> {
+    let Some(state) = app.try_state::<AppState>() else {
+        return Ok(());
+    };
+
+    state.scheduler.stop();
+    close_flavortime_session_from_state(&state, false).await
+}
+
+fn accumulate_sharing_seconds(
+    state: &AppState,
+    session_active: bool,
+) -> Result<u64, FlavortimeError> {
+    accumulate_sharing_seconds_at(state, session_active, unix_now_secs())
+}
+
+fn accumulate_sharing_seconds_at(
+    state: &AppState,
+    session_active: bool,
+    now: u64,
+) -> Result<u64, FlavortimeError> {
+    let mut last_tick = lock(&state.last_sharing_tick)?;
+    let mut cfg = lock(&state.config)?;
+
+    if let Some(previous) = *last_tick {
+        let elapsed = now.saturating_sub(previous).min(120);
+        if elapsed > 0 && session_active {
+            cfg.sharing_active_seconds_total =
+                cfg.sharing_active_seconds_total.saturating_add(elapsed);
+            cfg.save()?;
+            with_session_log(state, |session| session.add_sharing(elapsed))?;
+        }
+    }
+
+    *last_tick = Some(now);
+    Ok(cfg.sharing_active_seconds_total)
+}
+
+async fn load_hackatime_snapshot(
+    state: &AppState,
+    slack_id: &str,
+    window_mode: &WindowMode,
+    max_staleness_secs: u64,
+) -> Result<HackatimeSnapshot, FlavortimeError> {
+    match fetch_hackatime_snapshot(state.services.hackatime.as_ref(), slack_id, window_mode).await {
+        Ok(fresh) => {
+            let snapshot = hackatime_snapshot(&fresh, false);
+            if let Err(err) = fresh.save() {
+                log::warn!("Failed to persist Hackatime snapshot cache: {err}");
+            }
+            *lock(&state.snapshot_cache)? = Some(fresh);
+            Ok(snapshot)
+        }
+        Err(err) if err.is_transient() => {
+            let cached = lock(&state.snapshot_cache)?
+                .clone()
+                .filter(|cache| cache.usable_for(slack_id, max_staleness_secs, unix_now_secs()));
+            match cached {
+                Some(cache) => {
+                    log::warn!("Hackatime unavailable, using cached snapshot: {err}");
+                    Ok(hackatime_snapshot(&cache, true))
+                }
+                None => Err(err),
+            }
+        }
+        Err(err) => Err(err),
+    }
+}
+
+async fn fetch_hackatime_snapshot(
+    hackatime: &dyn HackatimeApi,
+    slack_id: &str,
+    window_mode: &WindowMode,
+) -> Result<SnapshotCache, FlavortimeError> {
+    let window = tracking_window(window_mode);
+    let projects = hackatime
+        .user_projects_details(
+            slack_id,
+            Some(window.start_rfc3339.as_str()),
+            Some(window.end_rfc3339.as_str()),
+        )
+        .await?;
+    Ok(SnapshotCache::new(
+        slack_id,
+        projects,
+        window.start_unix,
+        unix_now_secs(),
+    ))
+}
+
+fn hackatime_snapshot(cache: &SnapshotCache, stale: bool) -> HackatimeSnapshot {
+    let total_seconds = cache
+        .projects
+        .iter()
+        .map(|project| project.total_seconds.max(0.0))
+        .sum::<f64>();
+    let total_hours = total_seconds / 3600.0;
+
+    let latest = latest_project(&cache.projects, Some(cache.window_start));
+    let heartbeat_idle = latest
+        .as_ref()
+        .map(|(_, unix_time)| cache.fetched_at.saturating_sub(*unix_time) > 180)
+        .unwrap_or(true);
+
+    let current_project = latest.map(|(name, _)| {
+        let entries = || cache.projects.iter().filter(|project| project.name == name);
+        let project_seconds = entries()
+            .map(|project| project.total_seconds.max(0.0))
+            .sum::<f64>();
+        Project {
+            name: name.to_string(),
+            public_name: Some(name.to_string()),
+            hours: project_seconds / 3600.0,
+            language: entries().find_map(|project| project.languages.first().cloned()),
+        }
+    });
+
+    HackatimeSnapshot {
+        current_project,
+        total_hours,
+        heartbeat_idle,
+        stale,
+        fetched_at: cache.fetched_at,
+    }
+}
+
+fn clear_snapshot_cache(state: &AppState) -> Result<(), FlavortimeError> {
+    *lock(&state.snapshot_cache)? = None;
+    SnapshotCache::clear();
+    Ok(())
+}
+
+async fn ensure_flavortime_session_id(
+    state: &AppState,
+    api_key: &str,
+) -> Result<String, FlavortimeError> {
+    if let Some(existing) = lock(&state.flavortime_session_id)?.clone() {
+        return Ok(existing);
+    }
+
+    rotate_flavortime_session_id(state, api_key).await
+}
+
+async fn rotate_flavortime_session_id(
+    state: &AppState,
+    api_key: &str,
+) -> Result<String, FlavortimeError> {
+    let metadata = flavortown::session_metadata();
+    let session_id = state
+        .services
+        .flavortown
+        .create_session(api_key, &metadata)
+        .await?;
+    *lock(&state.flavortime_session_id)? = Some(session_id.clone());
+    reset_sharing_session(state)?;
+    {
+        let mut cfg = lock(&state.config)?;
+        cfg.flavortime_session_id = Some(session_id.clone());
+        cfg.flavortime_session_seen_at = unix_now_secs();
+        cfg.save()?;
+    }
+    restart_session_log(state)?;
     Ok(session_id)
 }
 
+fn touch_flavortime_session(state: &AppState) -> Result<(), FlavortimeError> {
+    let mut cfg = lock(&state.config)?;
+    if cfg.flavortime_session_id.is_some() {
+        cfg.flavortime_session_seen_at = unix_now_secs();
+        cfg.save()?;
+    }
+    Ok(())
+}
+
 async fn close_flavortime_session_from_state(
     state: &AppState,
     clear_local_session: bool,
-) -> Result<(), String> {
+) -> Result<(), FlavortimeError> {
+    end_session_log(state)?;
     let close_request = flavortime_close_request(state)?;
     if let Some((api_key, session_id, sharing_active_seconds_total)) = close_request {
         let metadata = flavortown::session_metadata();
-        let active_users = match flavortown::close_session(
-            &api_key,
-            &session_id,
-            sharing_active_seconds_total,
-            metadata.platform,
-            metadata.app_version,
-        )
-        .await?
+        let active_users = match state
+            .services
+            .flavortown
+            .close_session(
+                &api_key,
+                &session_id,
+                sharing_active_seconds_total,
+                &metadata,
+            )
+            .await?
         {
             flavortown::CloseOutcome::ActiveUsers(count) => count,
             flavortown::CloseOutcome::InvalidSessionId => 0,
         };
         let _ = active_users;
+
+        let mut cfg = lock(&state.config)?;
+        cfg.flavortime_session_id = None;
+        cfg.flavortime_session_seen_at = 0;
+        cfg.save()?;
     }
 
     if clear_local_session {
@@ -700,7 +1860,9 @@ async fn close_flavortime_session_from_state(
     Ok(())
 }
 
-fn flavortime_close_request(state: &AppState) -> Result<Option<(String, String, u64)>, String> {
+fn flavortime_close_request(
+    state: &AppState,
+) -> Result<Option<(String, String, u64)>, FlavortimeError> {
     let (auth_mode, api_key, sharing_active_seconds_total) = {
         let cfg = lock(&state.config)?;
         (
@@ -729,10 +1891,12 @@ fn flavortime_close_request(state: &AppState) -> Result<Option<(String, String,
     Ok(Some((api_key, session_id, sharing_active_seconds_total)))
 }
 
-fn reset_sharing_session(state: &AppState) -> Result<(), String> {
+fn reset_sharing_session(state: &AppState) -> Result<(), FlavortimeError> {
     {
         let mut cfg = lock(&state.config)?;
         cfg.sharing_active_seconds_total = 0;
+        cfg.flavortime_session_id = None;
+        cfg.flavortime_session_seen_at = 0;
         cfg.save()?;
     }
 
@@ -740,7 +1904,10 @@ fn reset_sharing_session(state: &AppState) -> Result<(), String> {
     Ok(())
 }
 
-fn sync_discord(cfg: &Config, rpc: &Mutex<Option<DiscordPresenceManager>>) -> Result<(), String> {
+fn sync_discord(
+    cfg: &Config,
+    rpc: &Mutex<Option<DiscordPresenceManager>>,
+) -> Result<(), FlavortimeError> {
     if cfg.app_enabled {
         ensure_discord_client(rpc)?;
     }
@@ -749,12 +1916,19 @@ fn sync_discord(cfg: &Config, rpc: &Mutex<Option<DiscordPresenceManager>>) -> Re
     if let Some(client) = rpc.as_mut() {
         client.set_enabled(cfg.app_enabled);
         client.maybe_recover();
-        client.update(None, None, cfg.display_code(), cfg.show_referral_code);
+        client.set_options(presence_options(cfg));
+        client.update(
+            Activity::default(),
+            cfg.display_code(),
+            cfg.show_referral_code,
+        );
     }
     Ok(())
 }
 
-fn ensure_discord_client(rpc: &Mutex<Option<DiscordPresenceManager>>) -> Result<(), String> {
+fn ensure_discord_client(
+    rpc: &Mutex<Option<DiscordPresenceManager>>,
+) -> Result<(), FlavortimeError> {
     let mut rpc = lock(rpc)?;
     if rpc.is_none() {
         let mut client = DiscordPresenceManager::new(runtime().discord_client_id);
@@ -767,7 +1941,7 @@ fn ensure_discord_client(rpc: &Mutex<Option<DiscordPresenceManager>>) -> Result<
 fn maybe_ensure_discord_client(
     rpc: &Mutex<Option<DiscordPresenceManager>>,
     enabled: bool,
-) -> Result<(), String> {
+) -> Result<(), FlavortimeError> {
     if enabled {
         ensure_discord_client(rpc)?;
     }
@@ -778,7 +1952,7 @@ fn poll_discord_status(
     rpc: &Mutex<Option<DiscordPresenceManager>>,
     enabled: bool,
     force_refresh: bool,
-) -> Result<(bool, bool), String> {
+) -> Result<(bool, bool), FlavortimeError> {
     let mut rpc = lock(rpc)?;
     if let Some(client) = rpc.as_mut() {
         if enabled && force_refresh {
@@ -792,7 +1966,9 @@ fn poll_discord_status(
     }
 }
 
-fn retry_discord_connection(rpc: &Mutex<Option<DiscordPresenceManager>>) -> Result<(), String> {
+fn retry_discord_connection(
+    rpc: &Mutex<Option<DiscordPresenceManager>>,
+) -> Result<(), FlavortimeError> {
     let mut rpc = lock(rpc)?;
     if let Some(client) = rpc.as_mut() {
         client.reconnect_now();
@@ -846,10 +2022,8 @@ fn flatpak_discord_detected() -> bool {
     false
 }
 
-fn lock<T>(mutex: &Mutex<T>) -> Result<MutexGuard<'_, T>, String> {
-    mutex
-        .lock()
-        .map_err(|_| "Internal state lock failed".to_string())
+fn lock<T>(mutex: &Mutex<T>) -> Result<MutexGuard<'_, T>, FlavortimeError> {
+    mutex.lock().map_err(|_| FlavortimeError::Lock)
 }
 
 fn mode_name(mode: &Mode) -> &'static str {
@@ -860,6 +2034,47 @@ fn mode_name(mode: &Mode) -> &'static str {
     }
 }
 
+fn tracking_backend_name(backend: &TrackingBackend) -> &'static str {
+    match backend {
+        TrackingBackend::Hackatime => "hackatime",
+        TrackingBackend::WakaTime => "wakatime",
+    }
+}
+
+fn parse_utc_offset(value: &str) -> Result<i32, FlavortimeError> {
+    let invalid = || {
+        FlavortimeError::InvalidInput(format!(
+            "Invalid UTC offset `{value}`, expected something like +05:30"
+        ))
+    };
+
+    let raw = value.trim();
+    let raw = raw
+        .strip_prefix("UTC")
+        .or_else(|| raw.strip_prefix("GMT"))
+        .unwrap_or(raw);
+    if raw.is_empty() || raw == "Z" {
+        return Ok(0);
+    }
+
+    let (sign, digits) = match raw.split_at(1) {
+        ("+", rest) => (1, rest),
+        ("-", rest) => (-1, rest),
+        _ => return Err(invalid()),
+    };
+    let (hours, minutes) = match digits.split_once(':') {
+        Some((hours, minutes)) => (hours, minutes),
+        None if digits.len() == 4 => digits.split_at(2),
+        None => (digits, "0"),
+    };
+    let hours = hours.parse::<i32>().map_err(|_| invalid())?;
+    let minutes = minutes.parse::<i32>().map_err(|_| invalid())?;
+    if !(0..60).contains(&minutes) || hours * 60 + minutes > 14 * 60 {
+        return Err(invalid());
+    }
+    Ok(sign * (hours * 60 + minutes))
+}
+
 fn trimmed(value: Option<String>) -> Option<String> {
     value.and_then(|raw| {
         let trimmed = raw.trim();
@@ -871,12 +2086,23 @@ fn stringify(err: impl ToString) -> String {
     err.to_string()
 }
 
-fn unix_now_secs() -> u64 {
+fn system_error(err: impl ToString) -> FlavortimeError {
+    FlavortimeError::System(err.to_string())
+}
+
+fn updater_error(err: impl ToString) -> FlavortimeError {
+    FlavortimeError::Updater(err.to_string())
+}
+
+pub(crate) fn unix_now_secs() -> u64 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .map_or(0, |duration| duration.as_secs())
 }
 
-fn required<T>(value: Option<T>, message: &str) -> Result<T, String> {
-    value.ok_or_else(|| message.to_string())
+fn required<T>(value: Option<T>) -> Result<T, FlavortimeError> {
+    value.ok_or(FlavortimeError::NotSignedIn)
 }
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,93 @@
+use super::*;
+use std::collections::HashSet;
+
+#[test]
+fn login_stores_account_and_opens_session() {
+    let harness = logged_in();
+    let cfg = harness.state.config.lock().unwrap();
+
+    assert!(cfg.auth_mode == Mode::Hackatime);
+    assert_eq!(cfg.slack_id.as_deref(), Some(SLACK_ID));
+    assert_eq!(cfg.flavortown_api_key.as_deref(), Some(API_KEY));
+    assert_eq!(cfg.available_referral_codes.len(), 2);
+    assert_eq!(cfg.selected_referral_code.as_deref(), Some("CUSTOM"));
+    assert!(cfg.show_time_tracking);
+    drop(cfg);
+
+    assert_eq!(session_id(&harness.state).as_deref(), Some("session-1"));
+}
+
+#[test]
+fn login_rejects_blank_key() {
+    let harness = harness();
+    let err = block_on(login_with_flavortown_api_key_from_state(
+        &harness.state,
+        "  ",
+    ))
+    .expect_err("blank key");
+    assert!(matches!(err, FlavortimeError::InvalidInput(_)));
+}
+
+#[test]
+fn login_with_rejected_key_leaves_config_untouched() {
+    let harness = harness_with(FakeFlavortown {
+        rejected_keys: HashSet::from([API_KEY.to_string()]),
+        ..FakeFlavortown::default()
+    });
+
+    let err = block_on(login_with_flavortown_api_key_from_state(
+        &harness.state,
+        API_KEY,
+    ))
+    .expect_err("rejected key");
+    assert!(matches!(err, FlavortimeError::Unauthorized(_)));
+
+    let cfg = harness.state.config.lock().unwrap();
+    assert!(cfg.auth_mode == Mode::None);
+    assert!(cfg.slack_id.is_none());
+    assert!(session_id(&harness.state).is_none());
+}
+
+#[test]
+fn adult_mode_clears_hackatime_account() {
+    let harness = logged_in();
+    login_as_adult_from_state(&harness.state).expect("adult login");
+
+    let cfg = harness.state.config.lock().unwrap();
+    assert!(cfg.auth_mode == Mode::Adult);
+    assert!(cfg.slack_id.is_none());
+    assert!(cfg.flavortown_api_key.is_none());
+    assert!(cfg.available_referral_codes.is_empty());
+    assert!(cfg.selected_referral_code.is_none());
+    assert!(!cfg.show_time_tracking);
+    assert_eq!(cfg.sharing_active_seconds_total, 0);
+    drop(cfg);
+
+    assert!(session_id(&harness.state).is_none());
+    assert_eq!(
+        block_on(send_flavortown_heartbeat_from_state(&harness.state)).expect("heartbeat"),
+        0
+    );
+}
+
+#[test]
+fn logout_closes_session_and_resets_config() {
+    let harness = logged_in();
+    accumulate_sharing_seconds_at(&harness.state, true, 1_000).expect("tick");
+    accumulate_sharing_seconds_at(&harness.state, true, 1_045).expect("tick");
+
+    block_on(logout_from_state(&harness.state)).expect("logout");
+
+    assert_eq!(
+        *harness.flavortown.closed.lock().unwrap(),
+        vec![("session-1".to_string(), 45)]
+    );
+    let cfg = harness.state.config.lock().unwrap();
+    assert!(cfg.auth_mode == Mode::None);
+    assert!(cfg.slack_id.is_none());
+    assert!(cfg.flavortown_api_key.is_none());
+    drop(cfg);
+
+    assert!(session_id(&harness.state).is_none());
+    assert!(harness.state.last_sharing_tick.lock().unwrap().is_none());
+}
@@ -0,0 +1,154 @@
+use super::*;
+use crate::services::hackatime::CodingBlock;
+
+#[test]
+fn history_tracks_streaks_and_only_refetches_today() {
+    let harness = logged_in();
+    set_history_days_from_state(&harness.state, 3).expect("history days");
+    set_daily_goal_from_state(&harness.state, 90).expect("daily goal");
+    assert!(set_history_days_from_state(&harness.state, 0).is_err());
+    assert!(set_daily_goal_from_state(&harness.state, 24 * 60 + 1).is_err());
+
+    harness.hackatime.requested_ends.lock().unwrap().clear();
+    let summary = block_on(refresh_history_from_state(&harness.state)).expect("history");
+    assert_eq!(summary.days.len(), 3);
+    assert!(summary.days.iter().all(|day| day.seconds == 5400.0));
+    assert_eq!(summary.streaks.current, 3);
+    assert_eq!(summary.streaks.longest, 3);
+    assert!(summary.streaks.goal_met_today);
+    assert_eq!(harness.hackatime.requested_ends.lock().unwrap().len(), 3);
+    assert!(!history_refresh_due(&harness.state));
+
+    harness.hackatime.requested_ends.lock().unwrap().clear();
+    block_on(refresh_history_from_state(&harness.state)).expect("history again");
+    assert_eq!(harness.hackatime.requested_ends.lock().unwrap().len(), 1);
+
+    set_daily_goal_from_state(&harness.state, 120).expect("daily goal");
+    let summary = history_summary(&harness.state).expect("summary");
+    assert_eq!(summary.streaks.current, 0);
+    assert_eq!(summary.streaks.longest, 0);
+
+    block_on(logout_from_state(&harness.state)).expect("logout");
+    assert!(history_summary(&harness.state)
+        .expect("summary")
+        .days
+        .is_empty());
+}
+
+#[test]
+fn stats_aggregate_ranges_and_cache_closed_days() {
+    let harness = logged_in();
+    harness.hackatime.requested_ends.lock().unwrap().clear();
+
+    let week = block_on(get_stats_from_state(&harness.state, StatsRange::Week)).expect("stats");
+    assert_eq!(week.days.len(), 7);
+    assert_eq!(week.total_seconds, 7.0 * 5400.0);
+    assert_eq!(week.top_projects[0].name, "flavortime");
+    assert_eq!(harness.hackatime.requested_ends.lock().unwrap().len(), 7);
+
+    harness.hackatime.requested_ends.lock().unwrap().clear();
+    let month = block_on(get_stats_from_state(&harness.state, StatsRange::Month)).expect("stats");
+    assert_eq!(month.days.len(), 30);
+    assert_eq!(
+        harness.hackatime.requested_ends.lock().unwrap().len(),
+        30 - 6
+    );
+
+    harness.hackatime.requested_ends.lock().unwrap().clear();
+    block_on(get_stats_from_state(&harness.state, StatsRange::Month)).expect("stats again");
+    assert_eq!(harness.hackatime.requested_ends.lock().unwrap().len(), 1);
+}
+
+#[test]
+fn export_writes_filtered_days_to_the_chosen_path() {
+    let harness = logged_in();
+    let today = chrono::Local::now().date_naive();
+    let start = (today - chrono::Days::new(2))
+        .format("%Y-%m-%d")
+        .to_string();
+    let end = today.format("%Y-%m-%d").to_string();
+    let target = harness.dir.path().join("report");
+
+    let summary = block_on(export_history_from_state(
+        &harness.state,
+        &start,
+        &end,
+        ExportFormat::Csv,
+        target.to_str().unwrap(),
+        &["FLAVORTIME".to_string()],
+    ))
+    .expect("export");
+    assert_eq!(summary.days, 3);
+    assert_eq!(summary.entries, 3);
+    assert!(summary.path.ends_with("report.csv"));
+
+    let csv = std::fs::read_to_string(target.with_extension("csv")).expect("csv");
+    assert_eq!(csv.lines().count(), 4);
+    assert!(csv
+        .lines()
+        .skip(1)
+        .all(|line| line.contains(",flavortime,")));
+
+    let relative = block_on(export_history_from_state(
+        &harness.state,
+        &start,
+        &end,
+        ExportFormat::Json,
+        "report.json",
+        &[],
+    ));
+    assert!(matches!(relative, Err(FlavortimeError::InvalidInput(_))));
+
+    let tomorrow = (today + chrono::Days::new(1))
+        .format("%Y-%m-%d")
+        .to_string();
+    let future = block_on(export_history_from_state(
+        &harness.state,
+        &tomorrow,
+        &tomorrow,
+        ExportFormat::Ics,
+        target.to_str().unwrap(),
+        &[],
+    ));
+    assert!(matches!(future, Err(FlavortimeError::InvalidInput(_))));
+}
+
+#[test]
+fn calendar_export_uses_coding_blocks_when_the_backend_has_them() {
+    let harness = logged_in();
+    let today = chrono::Local::now().date_naive();
+    let date = today.format("%Y-%m-%d").to_string();
+    let target = harness.dir.path().join("report.ics");
+    let export = || {
+        block_on(export_history_from_state(
+            &harness.state,
+            &date,
+            &date,
+            ExportFormat::Ics,
+            target.to_str().unwrap(),
+            &["flavortime".to_string()],
+        ))
+        .expect("export");
+        std::fs::read_to_string(&target).expect("ics")
+    };
+
+    let ics = export();
+    assert_eq!(ics.matches("BEGIN:VEVENT").count(), 1);
+    assert!(ics.contains("DTSTART;VALUE=DATE:"));
+
+    *harness.hackatime.blocks.lock().unwrap() = Some(vec![
+        CodingBlock {
+            project: "flavortime".to_string(),
+            start_unix: 1_772_359_200,
+            end_unix: 1_772_361_000,
+        },
+        CodingBlock {
+            project: "homework".to_string(),
+            start_unix: 1_772_362_800,
+            end_unix: 1_772_364_600,
+        },
+    ]);
+    let ics = export();
+    assert_eq!(ics.matches("BEGIN:VEVENT").count(), 1);
+    assert!(ics.contains("DTSTART:20260301T100000Z\r\nDTEND:20260301T103000Z\r\n"));
+}
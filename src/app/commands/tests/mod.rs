@@ -0,0 +1,9 @@
+use super::*;
+use crate::app::test_support::*;
+use tauri::async_runtime::block_on;
+
+mod auth;
+mod history;
+mod presence;
+mod sharing;
+mod tracking;
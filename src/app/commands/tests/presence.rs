@@ -0,0 +1,133 @@
+use super::*;
+use crate::data::config::{PrivacyAction, PrivacyMatch};
+
+#[test]
+fn presence_template_is_validated_and_previewed() {
+    let harness = logged_in();
+    block_on(get_hackatime_data_from_state(&harness.state)).expect("hackatime data");
+
+    let template = PresenceTemplate {
+        details: "Cooking {project}[ in {language}]".to_string(),
+        state: "{project_hours} on it".to_string(),
+        ..PresenceTemplate::default()
+    };
+    let preview =
+        preview_presence_from_state(&harness.state, Some(template.clone())).expect("preview");
+    assert_eq!(
+        preview.presence.details.as_deref(),
+        Some("Cooking flavortime in Rust")
+    );
+    assert_eq!(preview.presence.state.as_deref(), Some("1h 0m on it"));
+    assert!(harness.state.config.lock().unwrap().presence_template == PresenceTemplate::default());
+
+    let unknown = PresenceTemplate {
+        details: "{nope}".to_string(),
+        ..PresenceTemplate::default()
+    };
+    let err = set_presence_template_from_state(&harness.state, Some(unknown)).err();
+    assert!(matches!(err, Some(FlavortimeError::InvalidInput(_))));
+
+    let too_long = PresenceTemplate {
+        button_label: "Join me on Flavortown with {referral_code}".to_string(),
+        ..PresenceTemplate::default()
+    };
+    let err = set_presence_template_from_state(&harness.state, Some(too_long)).err();
+    assert!(matches!(err, Some(FlavortimeError::InvalidInput(_))));
+
+    set_presence_template_from_state(&harness.state, Some(template.clone())).expect("save");
+    assert!(harness.state.config.lock().unwrap().presence_template == template);
+    set_presence_template_from_state(&harness.state, None).expect("reset");
+    assert!(harness.state.config.lock().unwrap().presence_template == PresenceTemplate::default());
+}
+
+#[test]
+fn presence_profiles_follow_setting_changes() {
+    let harness = logged_in();
+    update_presence_profiles_from_state(&harness.state, |cfg| cfg.create_profile("focus"))
+        .expect("create focus");
+    {
+        let mut cfg = harness.state.config.lock().unwrap();
+        cfg.show_time_tracking = false;
+        cfg.capture_active_profile();
+    }
+    let profiles =
+        update_presence_profiles_from_state(&harness.state, |cfg| cfg.create_profile("school"))
+            .expect("create school");
+    assert_eq!(profiles.profiles, vec!["focus", "school"]);
+    assert_eq!(profiles.active.as_deref(), Some("school"));
+
+    set_presence_template_from_state(
+        &harness.state,
+        Some(PresenceTemplate {
+            details: "Studying".to_string(),
+            ..PresenceTemplate::default()
+        }),
+    )
+    .expect("template");
+
+    activate_presence_profile_from_state(&harness.state, Some("focus")).expect("focus");
+    {
+        let cfg = harness.state.config.lock().unwrap();
+        assert!(!cfg.show_time_tracking);
+        assert!(cfg.presence_template == PresenceTemplate::default());
+    }
+
+    activate_presence_profile_from_state(&harness.state, Some("school")).expect("school");
+    {
+        let cfg = harness.state.config.lock().unwrap();
+        assert_eq!(cfg.presence_template.details, "Studying");
+        assert!(!cfg.show_time_tracking);
+    }
+
+    let profiles = activate_presence_profile_from_state(&harness.state, None).expect("none");
+    assert!(profiles.active.is_none());
+    assert_eq!(profiles.profiles.len(), 2);
+}
+
+#[test]
+fn privacy_rules_rename_or_hide_the_discord_project() {
+    let harness = logged_in();
+    let data = block_on(get_hackatime_data_from_state(&harness.state)).expect("hackatime data");
+    assert_eq!(
+        activity_from_data(&data).project.as_deref(),
+        Some("flavortime")
+    );
+
+    let alias = PrivacyRule {
+        pattern: "flavor*".to_string(),
+        matcher: PrivacyMatch::Glob,
+        action: PrivacyAction::Alias {
+            alias: "Side project".to_string(),
+        },
+    };
+    set_privacy_rules_from_state(&harness.state, vec![alias]).expect("alias rule");
+    let data = harness.state.snapshots.latest().expect("republished");
+    let project = data.current_project.as_ref().expect("project");
+    assert_eq!(project.name, "flavortime");
+    assert_eq!(project.public_name.as_deref(), Some("Side project"));
+    assert_eq!(
+        activity_from_data(&data).project.as_deref(),
+        Some("Side project")
+    );
+
+    let hide = PrivacyRule {
+        pattern: "flavortime".to_string(),
+        matcher: PrivacyMatch::Exact,
+        action: PrivacyAction::Hide,
+    };
+    set_privacy_rules_from_state(&harness.state, vec![hide]).expect("hide rule");
+    let data = block_on(get_hackatime_data_from_state(&harness.state)).expect("hackatime data");
+    let activity = activity_from_data(&data);
+    assert!(activity.project.is_none());
+    assert!(activity.project_hours.is_none());
+    assert_eq!(activity.hours, Some(1.5));
+
+    let invalid = PrivacyRule {
+        pattern: "[".to_string(),
+        matcher: PrivacyMatch::Regex,
+        action: PrivacyAction::Secret,
+    };
+    let err = set_privacy_rules_from_state(&harness.state, vec![invalid]).err();
+    assert!(matches!(err, Some(FlavortimeError::InvalidInput(_))));
+    assert_eq!(harness.state.config.lock().unwrap().privacy_rules.len(), 1);
+}
@@ -0,0 +1,162 @@
+use super::*;
+
+#[test]
+fn heartbeat_reports_active_users() {
+    let harness = logged_in();
+    let count = block_on(send_flavortown_heartbeat_from_state(&harness.state)).expect("heartbeat");
+
+    assert_eq!(count, 7);
+    assert_eq!(
+        *harness.flavortown.heartbeats.lock().unwrap(),
+        vec![("session-1".to_string(), 0)]
+    );
+}
+
+#[test]
+fn heartbeat_rotates_expired_session() {
+    let harness = logged_in();
+    accumulate_sharing_seconds_at(&harness.state, true, 1_000).expect("tick");
+    accumulate_sharing_seconds_at(&harness.state, true, 1_060).expect("tick");
+    harness.flavortown.expire("session-1");
+
+    let count = block_on(send_flavortown_heartbeat_from_state(&harness.state)).expect("heartbeat");
+
+    assert_eq!(count, 7);
+    assert_eq!(session_id(&harness.state).as_deref(), Some("session-2"));
+    assert_eq!(
+        *harness.flavortown.heartbeats.lock().unwrap(),
+        vec![("session-2".to_string(), 0)]
+    );
+    assert_eq!(
+        harness
+            .state
+            .config
+            .lock()
+            .unwrap()
+            .sharing_active_seconds_total,
+        0
+    );
+}
+
+#[test]
+fn heartbeat_fails_when_rotated_session_is_rejected() {
+    let harness = harness_with(FakeFlavortown {
+        expire_every_session: true,
+        ..FakeFlavortown::default()
+    });
+    block_on(login_with_flavortown_api_key_from_state(
+        &harness.state,
+        API_KEY,
+    ))
+    .expect("login");
+
+    let err = block_on(send_flavortown_heartbeat_from_state(&harness.state))
+        .expect_err("rejected session");
+    assert_eq!(err, FlavortimeError::InvalidSession);
+}
+
+#[test]
+fn sharing_seconds_accumulate_with_cap() {
+    let harness = logged_in();
+    let state = &harness.state;
+
+    assert_eq!(
+        accumulate_sharing_seconds_at(state, true, 1_000).unwrap(),
+        0
+    );
+    assert_eq!(
+        accumulate_sharing_seconds_at(state, true, 1_030).unwrap(),
+        30
+    );
+    assert_eq!(
+        accumulate_sharing_seconds_at(state, true, 1_530).unwrap(),
+        150
+    );
+    assert_eq!(
+        accumulate_sharing_seconds_at(state, false, 1_590).unwrap(),
+        150
+    );
+    assert_eq!(
+        accumulate_sharing_seconds_at(state, true, 1_590).unwrap(),
+        150
+    );
+    assert_eq!(
+        accumulate_sharing_seconds_at(state, true, 1_650).unwrap(),
+        210
+    );
+}
+
+#[test]
+fn session_log_records_sharing_and_heartbeats_per_session() {
+    let harness = logged_in();
+    accumulate_sharing_seconds_at(&harness.state, true, 1_000).expect("tick");
+    accumulate_sharing_seconds_at(&harness.state, true, 1_090).expect("tick");
+    block_on(send_flavortown_heartbeat_from_state(&harness.state)).expect("heartbeat");
+    harness.flavortown.expire("session-1");
+    block_on(send_flavortown_heartbeat_from_state(&harness.state)).expect("rotated heartbeat");
+    accumulate_sharing_seconds_at(&harness.state, true, 1_120).expect("tick");
+    accumulate_sharing_seconds_at(&harness.state, true, 1_150).expect("tick");
+
+    let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+    let live = get_sessions_from_state(&harness.state, &today, &today).expect("sessions");
+    assert_eq!(live.sessions.len(), 2);
+    assert_eq!(live.sessions[1].counters.sharing_seconds, 30);
+
+    block_on(logout_from_state(&harness.state)).expect("logout");
+    let report = get_sessions_from_state(&harness.state, &today, &today).expect("sessions");
+    assert_eq!(report.sharing_seconds, 120);
+    let first = &report.sessions[0];
+    assert_eq!(first.counters.sharing_seconds, 90);
+    assert_eq!(first.counters.heartbeats_ok, 1);
+    assert!(first.ended_at.is_some());
+    assert_eq!(report.sessions[1].counters.heartbeats_ok, 1);
+    assert!(report.sessions[1].ended_at.is_some());
+
+    assert!(get_sessions_from_state(&harness.state, &today, "2000-01-01").is_err());
+    assert!(get_sessions_from_state(&harness.state, "yesterday", &today).is_err());
+}
+
+#[test]
+fn restart_resumes_persisted_session_and_sharing_total() {
+    let harness = logged_in();
+    accumulate_sharing_seconds_at(&harness.state, true, 1_000).expect("tick");
+    accumulate_sharing_seconds_at(&harness.state, true, 1_080).expect("tick");
+    block_on(send_flavortown_heartbeat_from_state(&harness.state)).expect("heartbeat");
+
+    let restart = |harness: &Harness| {
+        let services = harness.state.services.clone();
+        AppState::with_services(Config::load(), services)
+    };
+
+    let resumed = restart(&harness);
+    assert_eq!(session_id(&resumed).as_deref(), Some("session-1"));
+    assert_eq!(
+        block_on(send_flavortown_heartbeat_from_state(&resumed)).expect("heartbeat"),
+        7
+    );
+    assert_eq!(
+        harness.flavortown.heartbeats.lock().unwrap().last(),
+        Some(&("session-1".to_string(), 80))
+    );
+
+    harness.flavortown.expire("session-1");
+    let rotated = restart(&harness);
+    block_on(send_flavortown_heartbeat_from_state(&rotated)).expect("rotated heartbeat");
+    assert_eq!(session_id(&rotated).as_deref(), Some("session-2"));
+    assert_eq!(
+        harness.flavortown.heartbeats.lock().unwrap().last(),
+        Some(&("session-2".to_string(), 0))
+    );
+
+    {
+        let mut cfg = rotated.config.lock().unwrap();
+        cfg.flavortime_session_seen_at = 1;
+        cfg.sharing_active_seconds_total = 500;
+        cfg.save().expect("save");
+    }
+    let expired = restart(&harness);
+    assert!(session_id(&expired).is_none());
+    let cfg = expired.config.lock().unwrap();
+    assert!(cfg.flavortime_session_id.is_none());
+    assert_eq!(cfg.sharing_active_seconds_total, 0);
+}
@@ -0,0 +1,166 @@
+use super::*;
+
+#[test]
+fn hackatime_data_requires_hackatime_login() {
+    let harness = harness();
+    let err = block_on(get_hackatime_data_from_state(&harness.state)).err();
+    assert_eq!(err, Some(FlavortimeError::NotSignedIn));
+
+    let harness = logged_in();
+    let data = block_on(get_hackatime_data_from_state(&harness.state)).expect("hackatime data");
+    let project = data.current_project.expect("current project");
+    assert_eq!(project.name, "flavortime");
+    assert!((project.hours - 1.0).abs() < f64::EPSILON);
+    assert!((data.total_hours - 1.5).abs() < f64::EPSILON);
+    assert!(!data.heartbeat_idle);
+}
+
+#[test]
+fn hackatime_data_is_broadcast_to_subscribers() {
+    let harness = logged_in();
+    let mut snapshots = harness.state.snapshots.subscribe();
+    assert!(harness.state.snapshots.latest().is_none());
+
+    let data = block_on(get_hackatime_data_from_state(&harness.state)).expect("hackatime data");
+    assert!(snapshots.has_changed().unwrap());
+    let update = snapshots.borrow_and_update().clone();
+    let broadcast = update.expect("published").expect("successful snapshot");
+    assert_eq!(broadcast.fetched_at, data.fetched_at);
+    assert!(harness.state.snapshots.latest().is_some());
+
+    harness
+        .hackatime
+        .fail_with(Some(FlavortimeError::Unauthorized("bad key".to_string())));
+    assert!(block_on(get_hackatime_data_from_state(&harness.state)).is_err());
+    assert!(matches!(
+        *snapshots.borrow_and_update(),
+        Some(Err(FlavortimeError::Unauthorized(_)))
+    ));
+
+    block_on(logout_from_state(&harness.state)).expect("logout");
+    assert!(snapshots.borrow_and_update().is_none());
+}
+
+#[test]
+fn hackatime_outage_serves_cached_snapshot_as_stale() {
+    let harness = logged_in();
+    let fresh = block_on(get_hackatime_data_from_state(&harness.state)).expect("fresh data");
+    assert!(!fresh.stale);
+
+    harness
+        .hackatime
+        .fail_with(Some(FlavortimeError::Network("offline".to_string())));
+    let stale = block_on(get_hackatime_data_from_state(&harness.state)).expect("cached data");
+    assert!(stale.stale);
+    assert_eq!(stale.fetched_at, fresh.fetched_at);
+    assert_eq!(
+        stale.current_project.map(|project| project.name).as_deref(),
+        Some("flavortime")
+    );
+
+    *harness.state.snapshot_cache.lock().unwrap() = None;
+    let reloaded = SnapshotCache::load().expect("persisted cache");
+    assert_eq!(reloaded.slack_id, SLACK_ID);
+}
+
+#[test]
+fn expired_or_rejected_snapshot_is_not_reused() {
+    let harness = logged_in();
+    block_on(get_hackatime_data_from_state(&harness.state)).expect("fresh data");
+
+    harness
+        .hackatime
+        .fail_with(Some(FlavortimeError::Unauthorized("bad key".to_string())));
+    let err = block_on(get_hackatime_data_from_state(&harness.state)).err();
+    assert!(matches!(err, Some(FlavortimeError::Unauthorized(_))));
+
+    set_max_snapshot_staleness_from_state(&harness.state, 0).expect("set staleness");
+    harness
+        .state
+        .snapshot_cache
+        .lock()
+        .unwrap()
+        .as_mut()
+        .unwrap()
+        .fetched_at -= 60;
+    harness
+        .hackatime
+        .fail_with(Some(FlavortimeError::Network("offline".to_string())));
+    let err = block_on(get_hackatime_data_from_state(&harness.state)).err();
+    assert!(matches!(err, Some(FlavortimeError::Network(_))));
+
+    let err = set_max_snapshot_staleness_from_state(&harness.state, 24 * 60 + 1).err();
+    assert!(matches!(err, Some(FlavortimeError::InvalidInput(_))));
+}
+
+#[test]
+fn window_mode_parses_offsets_and_validates_hours() {
+    let harness = logged_in();
+    set_window_mode_from_state(&harness.state, "custom", Some("+05:30".into()), Some(4))
+        .expect("custom window");
+    assert!(
+        harness.state.config.lock().unwrap().window_mode
+            == WindowMode::Custom {
+                utc_offset_minutes: 330,
+                day_start_hour: 4,
+            }
+    );
+
+    assert_eq!(parse_utc_offset("UTC-8"), Ok(-480));
+    assert_eq!(parse_utc_offset("-0930"), Ok(-570));
+    assert_eq!(parse_utc_offset("Z"), Ok(0));
+    assert!(parse_utc_offset("+15:00").is_err());
+    assert!(parse_utc_offset("05:30").is_err());
+
+    let err = set_window_mode_from_state(&harness.state, "custom", None, Some(24)).err();
+    assert!(matches!(err, Some(FlavortimeError::InvalidInput(_))));
+
+    set_window_mode_from_state(&harness.state, "local_day", None, None).expect("local day");
+    let data = block_on(get_hackatime_data_from_state(&harness.state)).expect("hackatime data");
+    assert!(data.window_mode == WindowMode::LocalDay);
+}
+
+#[test]
+fn wakatime_backend_requires_server_and_key() {
+    let harness = logged_in();
+
+    let err = set_tracking_backend_from_state(&harness.state, "wakatime", None, Some("key".into()))
+        .expect_err("missing url");
+    assert!(matches!(err, FlavortimeError::InvalidInput(_)));
+
+    let err = set_tracking_backend_from_state(
+        &harness.state,
+        "wakatime",
+        Some("https://wakapi.example/api/compat/wakatime/v1".into()),
+        None,
+    )
+    .expect_err("missing key");
+    assert!(matches!(err, FlavortimeError::InvalidInput(_)));
+
+    let err = set_tracking_backend_from_state(&harness.state, "toggl", None, None)
+        .expect_err("unknown backend");
+    assert!(matches!(err, FlavortimeError::InvalidInput(_)));
+
+    let cfg = harness.state.config.lock().unwrap();
+    assert!(cfg.tracking_backend == TrackingBackend::Hackatime);
+}
+
+#[test]
+fn wakatime_backend_is_persisted() {
+    let harness = logged_in();
+    set_tracking_backend_from_state(
+        &harness.state,
+        "wakatime",
+        Some("https://wakapi.example/api/compat/wakatime/v1/".into()),
+        Some("waka-key".into()),
+    )
+    .expect("select wakatime");
+
+    let cfg = harness.state.config.lock().unwrap();
+    assert!(cfg.tracking_backend == TrackingBackend::WakaTime);
+    assert_eq!(
+        cfg.wakatime_base_url.as_deref(),
+        Some("https://wakapi.example/api/compat/wakatime/v1")
+    );
+    assert_eq!(cfg.wakatime_api_key.as_deref(), Some("waka-key"));
+}
@@ -0,0 +1,241 @@
+use crate::data::config::data_dir;
+use crate::data::storage::write_atomic_private;
+use chacha20poly1305::aead::{rand_core::RngCore, OsRng};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+const CONNECT_ATTEMPTS: u32 = 20;
+const CONNECT_RETRY_DELAY: Duration = Duration::from_millis(100);
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+const READ_TIMEOUT: Duration = Duration::from_millis(500);
+const TOKEN_BYTES: usize = 32;
+
+pub struct InstanceGuard {
+    lock: File,
+    listener: TcpListener,
+    token: String,
+}
+
+pub enum Launch {
+    Primary(InstanceGuard),
+    Unguarded,
+    Forwarded,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Endpoint {
+    port: u16,
+    token: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Forwarded {
+    token: String,
+    args: Vec<String>,
+}
+
+pub fn acquire() -> Launch {
+    match try_acquire() {
+        Ok(Some(guard)) => Launch::Primary(guard),
+        Ok(None) => {
+            let args: Vec<String> = std::env::args().skip(1).collect();
+            if let Err(err) = forward(&args) {
+                eprintln!("Flavortime is already running but did not answer: {err}");
+            }
+            Launch::Forwarded
+        }
+        Err(err) => {
+            eprintln!("Single-instance guard unavailable: {err}");
+            Launch::Unguarded
+        }
+    }
+}
+
+// Each connection gets its own thread so a client that never writes cannot
+// hold up later launches.
+pub fn listen(app: &AppHandle, guard: InstanceGuard) {
+    let app = app.clone();
+    let token = Arc::<str>::from(guard.token);
+    thread::spawn(move || {
+        let _lock = guard.lock;
+        for stream in guard.listener.incoming().flatten() {
+            let (app, token) = (app.clone(), Arc::clone(&token));
+            thread::spawn(move || {
+                if read_forwarded(stream, &token).is_some_and(|args| wants_window(&args)) {
+                    show_main_window(&app);
+                }
+            });
+        }
+    });
+}
+
+pub fn wants_window(args: &[String]) -> bool {
+    args.iter().any(|arg| arg == "--show") || !args.iter().any(|arg| arg == "--hidden")
+}
+
+fn show_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+fn read_forwarded(stream: TcpStream, token: &str) -> Option<Vec<String>> {
+    let _ = stream.set_read_timeout(Some(READ_TIMEOUT));
+    let mut line = String::new();
+    BufReader::new(stream).read_line(&mut line).ok()?;
+    let forwarded = serde_json::from_str::<Forwarded>(&line).ok()?;
+    if forwarded.token != token {
+        log::warn!("Ignoring single-instance message with an invalid token");
+        return None;
+    }
+    Some(forwarded.args)
+}
+
+fn try_acquire() -> std::io::Result<Option<InstanceGuard>> {
+    let dir = data_dir();
+    fs::create_dir_all(&dir)?;
+    let lock = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(lock_path())?;
+
+    match lock.try_lock() {
+        Ok(()) => {}
+        Err(TryLockError::WouldBlock) => return Ok(None),
+        Err(TryLockError::Error(err)) => return Err(err),
+    }
+
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+    let mut secret = [0u8; TOKEN_BYTES];
+    OsRng.fill_bytes(&mut secret);
+    let endpoint = Endpoint {
+        port: listener.local_addr()?.port(),
+        token: secret.iter().map(|byte| format!("{byte:02x}")).collect(),
+    };
+    write_atomic_private(
+        &endpoint_path(),
+        serde_json::to_string(&endpoint)?.as_bytes(),
+    )
+    .map_err(|err| std::io::Error::other(err.to_string()))?;
+
+    Ok(Some(InstanceGuard {
+        lock,
+        listener,
+        token: endpoint.token,
+    }))
+}
+
+fn forward(args: &[String]) -> std::io::Result<()> {
+    let mut last_error = None;
+    for _ in 0..CONNECT_ATTEMPTS {
+        match connect() {
+            Ok((mut stream, token)) => {
+                let payload = serde_json::to_string(&Forwarded {
+                    token,
+                    args: args.to_vec(),
+                })?;
+                stream.write_all(payload.as_bytes())?;
+                return stream.write_all(b"\n");
+            }
+            Err(err) => {
+                last_error = Some(err);
+                thread::sleep(CONNECT_RETRY_DELAY);
+            }
+        }
+    }
+    Err(last_error.unwrap_or_else(|| std::io::Error::other("No running instance found")))
+}
+
+fn connect() -> std::io::Result<(TcpStream, String)> {
+    let endpoint: Endpoint = serde_json::from_str(&fs::read_to_string(endpoint_path())?)?;
+    let address = SocketAddr::from((Ipv4Addr::LOCALHOST, endpoint.port));
+    let stream = TcpStream::connect_timeout(&address, CONNECT_TIMEOUT)?;
+    Ok((stream, endpoint.token))
+}
+
+fn lock_path() -> PathBuf {
+    data_dir().join("instance.lock")
+}
+
+fn endpoint_path() -> PathBuf {
+    data_dir().join("instance.json")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::config::set_test_data_dir;
+
+    fn args(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    fn primary() -> (tempfile::TempDir, InstanceGuard) {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        set_test_data_dir(dir.path());
+        let guard = try_acquire().unwrap().expect("primary instance");
+        (dir, guard)
+    }
+
+    #[test]
+    fn second_launch_forwards_its_arguments() {
+        let (dir, guard) = primary();
+        assert!(try_acquire().unwrap().is_none());
+
+        let data_dir = dir.path().to_path_buf();
+        let sender = thread::spawn(move || {
+            set_test_data_dir(&data_dir);
+            forward(&args(&["--show"]))
+        });
+        let (stream, _) = guard.listener.accept().unwrap();
+        assert_eq!(
+            read_forwarded(stream, &guard.token),
+            Some(args(&["--show"]))
+        );
+        sender.join().unwrap().unwrap();
+    }
+
+    #[test]
+    fn messages_without_the_token_are_ignored() {
+        let (_dir, guard) = primary();
+        let port = guard.listener.local_addr().unwrap().port();
+
+        for payload in [r#"{"token":"guess","args":["--show"]}"#, r#"["--show"]"#] {
+            let mut stream = TcpStream::connect((Ipv4Addr::LOCALHOST, port)).unwrap();
+            writeln!(stream, "{payload}").unwrap();
+            let (stream, _) = guard.listener.accept().unwrap();
+            assert_eq!(read_forwarded(stream, &guard.token), None);
+        }
+    }
+
+    #[test]
+    fn silent_clients_time_out_quickly() {
+        let (_dir, guard) = primary();
+        let port = guard.listener.local_addr().unwrap().port();
+
+        let _silent = TcpStream::connect((Ipv4Addr::LOCALHOST, port)).unwrap();
+        let (stream, _) = guard.listener.accept().unwrap();
+        let started = std::time::Instant::now();
+        assert_eq!(read_forwarded(stream, &guard.token), None);
+        assert!(started.elapsed() < CONNECT_TIMEOUT);
+    }
+
+    #[test]
+    fn show_wins_over_hidden() {
+        assert!(wants_window(&[]));
+        assert!(wants_window(&args(&["--show"])));
+        assert!(!wants_window(&args(&["--hidden"])));
+        assert!(wants_window(&args(&["--hidden", "--show"])));
+    }
+}
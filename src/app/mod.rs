@@ -1,2 +1,8 @@
 pub mod commands;
+pub mod instance;
+pub mod scheduler;
+pub mod snapshot;
 pub mod state;
+
+#[cfg(test)]
+pub(crate) mod test_support;
@@ -0,0 +1,341 @@
+use super::commands::{
+    activity_from_data, get_hackatime_data_from_state, history_refresh_due,
+    refresh_history_from_state, send_flavortown_heartbeat_from_state,
+    update_discord_presence_from_state, HistorySummary,
+};
+use super::snapshot::SnapshotUpdate;
+use super::state::AppState;
+use crate::data::config::Mode;
+use crate::error::FlavortimeError;
+use crate::services::discord::Activity;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::Notify;
+
+pub const TICK_INTERVAL: Duration = Duration::from_secs(60);
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(60);
+pub const MAX_REFRESH_BACKOFF: Duration = Duration::from_secs(15 * 60);
+
+pub const HACKATIME_DATA_EVENT: &str = "hackatime-data";
+pub const HACKATIME_DATA_FAILED_EVENT: &str = "hackatime-data-failed";
+pub const FLAVORTOWN_HEARTBEAT_EVENT: &str = "flavortown-heartbeat";
+pub const FLAVORTOWN_HEARTBEAT_FAILED_EVENT: &str = "flavortown-heartbeat-failed";
+pub const PRESENCE_PROFILES_EVENT: &str = "presence-profiles";
+pub const HISTORY_EVENT: &str = "history";
+
+#[derive(Default)]
+pub struct Scheduler {
+    wake: Notify,
+    task: Mutex<Option<JoinHandle<()>>>,
+    last_heartbeat: Mutex<Option<Instant>>,
+    backoff: Mutex<Backoff>,
+}
+
+#[derive(Default)]
+struct Backoff {
+    failures: u32,
+    retry_at: Option<Instant>,
+}
+
+#[derive(Default)]
+pub struct TickReport {
+    pub heartbeat: Option<Result<u64, FlavortimeError>>,
+    pub history: Option<HistorySummary>,
+}
+
+impl Scheduler {
+    pub fn wake(&self) {
+        if let Ok(mut backoff) = self.backoff.lock() {
+            backoff.retry_at = None;
+        }
+        self.wake.notify_one();
+    }
+
+    pub fn stop(&self) {
+        if let Some(task) = self.task.lock().ok().and_then(|mut task| task.take()) {
+            task.abort();
+        }
+    }
+
+    fn heartbeat_due(&self) -> bool {
+        let Ok(mut last_heartbeat) = self.last_heartbeat.lock() else {
+            return true;
+        };
+        let now = Instant::now();
+        if last_heartbeat.is_some_and(|last| now.duration_since(last) < HEARTBEAT_INTERVAL) {
+            return false;
+        }
+        *last_heartbeat = Some(now);
+        true
+    }
+
+    fn refresh_due(&self) -> bool {
+        self.backoff.lock().map_or(true, |backoff| {
+            backoff.retry_at.is_none_or(|at| Instant::now() >= at)
+        })
+    }
+
+    fn record_refresh(&self, result: Result<(), &FlavortimeError>) {
+        let Ok(mut backoff) = self.backoff.lock() else {
+            return;
+        };
+        let Err(err) = result else {
+            *backoff = Backoff::default();
+            return;
+        };
+
+        backoff.failures = backoff.failures.saturating_add(1);
+        let mut delay = TICK_INTERVAL
+            .saturating_mul(1 << backoff.failures.min(5).saturating_sub(1))
+            .min(MAX_REFRESH_BACKOFF);
+        if let FlavortimeError::RateLimited {
+            retry_after: Some(seconds),
+        } = err
+        {
+            delay = delay.max(Duration::from_secs(*seconds));
+        }
+        backoff.retry_at = Some(Instant::now() + delay);
+    }
+
+    fn reset_backoff(&self) {
+        if let Ok(mut backoff) = self.backoff.lock() {
+            *backoff = Backoff::default();
+        }
+    }
+
+    async fn wait_for_wake(&self, timeout: Duration) -> bool {
+        tokio::time::timeout(timeout, self.wake.notified())
+            .await
+            .is_ok()
+    }
+}
+
+pub fn start(app: &AppHandle) {
+    let Some(state) = app.try_state::<AppState>() else {
+        return;
+    };
+    let Ok(mut task) = state.scheduler.task.lock() else {
+        return;
+    };
+    if task.is_some() {
+        return;
+    }
+
+    let mut snapshots = state.snapshots.subscribe();
+    let listener_handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        while snapshots.changed().await.is_ok() {
+            let update = snapshots.borrow_and_update().clone();
+            fan_out(&listener_handle, &update);
+        }
+    });
+
+    let app_handle = app.clone();
+    *task = Some(tauri::async_runtime::spawn(async move {
+        loop {
+            let Some(state) = app_handle.try_state::<AppState>() else {
+                tokio::time::sleep(Duration::from_secs(2)).await;
+                continue;
+            };
+
+            let report = tick(&state).await;
+            publish(&app_handle, &report);
+            state.scheduler.wait_for_wake(TICK_INTERVAL).await;
+        }
+    }));
+}
+
+pub async fn tick(state: &AppState) -> TickReport {
+    let auth_mode = state
+        .config
+        .lock()
+        .ok()
+        .map(|cfg| cfg.auth_mode.clone())
+        .unwrap_or_default();
+    if !matches!(auth_mode, Mode::Hackatime) {
+        state.scheduler.reset_backoff();
+        return TickReport::default();
+    }
+
+    if state.scheduler.refresh_due() {
+        let refresh = get_hackatime_data_from_state(state).await;
+        if let Err(err) = &refresh {
+            log::warn!("Scheduled Hackatime refresh failed: {err}");
+        }
+        state.scheduler.record_refresh(refresh.as_ref().map(|_| ()));
+    }
+
+    let heartbeat = if state.scheduler.heartbeat_due() {
+        let heartbeat = send_flavortown_heartbeat_from_state(state).await;
+        if let Err(err) = &heartbeat {
+            log::warn!("Scheduled Flavortown heartbeat failed: {err}");
+        }
+        Some(heartbeat)
+    } else {
+        None
+    };
+
+    let history = if history_refresh_due(state) {
+        refresh_history_from_state(state)
+            .await
+            .map_err(|err| log::warn!("Scheduled history refresh failed: {err}"))
+            .ok()
+    } else {
+        None
+    };
+
+    TickReport { heartbeat, history }
+}
+
+fn fan_out(app: &AppHandle, update: &SnapshotUpdate) {
+    let Some(update) = update else {
+        return;
+    };
+
+    if let Some(state) = app.try_state::<AppState>() {
+        let activity = match update {
+            Ok(data) => activity_from_data(data),
+            Err(_) => Activity::default(),
+        };
+        let presence = update_discord_presence_from_state(&state, activity);
+        if let Err(err) = presence {
+            log::warn!("Discord presence update from snapshot failed: {err}");
+        }
+    }
+
+    match update {
+        Ok(data) => {
+            let _ = app.emit(HACKATIME_DATA_EVENT, data.clone());
+        }
+        Err(err) => {
+            let _ = app.emit(HACKATIME_DATA_FAILED_EVENT, err.clone());
+        }
+    }
+}
+
+fn publish(app: &AppHandle, report: &TickReport) {
+    match &report.heartbeat {
+        Some(Ok(active_users)) => {
+            let _ = app.emit(FLAVORTOWN_HEARTBEAT_EVENT, *active_users);
+        }
+        Some(Err(err)) => {
+            let _ = app.emit(FLAVORTOWN_HEARTBEAT_FAILED_EVENT, err.clone());
+        }
+        None => {}
+    }
+
+    if let Some(history) = &report.history {
+        let _ = app.emit(HISTORY_EVENT, history.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::commands::{login_with_flavortown_api_key_from_state, logout_from_state};
+    use crate::app::test_support::*;
+    use tauri::async_runtime::block_on;
+
+    const NO_WAKE: Duration = Duration::from_millis(50);
+
+    fn failures(scheduler: &Scheduler) -> u32 {
+        scheduler.backoff.lock().unwrap().failures
+    }
+
+    fn retry_in(scheduler: &Scheduler) -> Duration {
+        let retry_at = scheduler
+            .backoff
+            .lock()
+            .unwrap()
+            .retry_at
+            .expect("backing off");
+        retry_at.saturating_duration_since(Instant::now())
+    }
+
+    fn elapse(scheduler: &Scheduler) {
+        scheduler.backoff.lock().unwrap().retry_at = Some(Instant::now());
+        *scheduler.last_heartbeat.lock().unwrap() = None;
+    }
+
+    #[test]
+    fn failed_refreshes_back_off_without_stopping_heartbeats() {
+        let harness = logged_in();
+        let scheduler = &harness.state.scheduler;
+        harness
+            .hackatime
+            .fail_with(Some(FlavortimeError::Network("offline".to_string())));
+
+        let report = block_on(tick(&harness.state));
+        assert!(matches!(report.heartbeat, Some(Ok(7))));
+        assert_eq!(failures(scheduler), 1);
+        assert!(retry_in(scheduler) > TICK_INTERVAL - Duration::from_secs(5));
+
+        *scheduler.last_heartbeat.lock().unwrap() = None;
+        let report = block_on(tick(&harness.state));
+        assert!(matches!(report.heartbeat, Some(Ok(7))));
+        assert_eq!(failures(scheduler), 1);
+
+        elapse(scheduler);
+        block_on(tick(&harness.state));
+        assert_eq!(failures(scheduler), 2);
+        assert!(retry_in(scheduler) > TICK_INTERVAL * 2 - Duration::from_secs(5));
+
+        harness
+            .hackatime
+            .fail_with(Some(FlavortimeError::RateLimited {
+                retry_after: Some(3_600),
+            }));
+        elapse(scheduler);
+        block_on(tick(&harness.state));
+        assert!(retry_in(scheduler) > MAX_REFRESH_BACKOFF);
+
+        harness.hackatime.fail_with(None);
+        elapse(scheduler);
+        block_on(tick(&harness.state));
+        assert_eq!(failures(scheduler), 0);
+        assert!(scheduler.refresh_due());
+    }
+
+    #[test]
+    fn ticks_pause_while_signed_out_and_resume_on_login() {
+        let harness = harness();
+        let scheduler = &harness.state.scheduler;
+
+        let report = block_on(tick(&harness.state));
+        assert!(report.heartbeat.is_none());
+        assert!(report.history.is_none());
+        assert!(harness.hackatime.requested_ends.lock().unwrap().is_empty());
+        assert!(!block_on(scheduler.wait_for_wake(NO_WAKE)));
+
+        block_on(login_with_flavortown_api_key_from_state(
+            &harness.state,
+            API_KEY,
+        ))
+        .expect("login");
+        assert!(block_on(scheduler.wait_for_wake(Duration::from_secs(5))));
+        harness.hackatime.requested_ends.lock().unwrap().clear();
+
+        let report = block_on(tick(&harness.state));
+        assert!(matches!(report.heartbeat, Some(Ok(7))));
+        assert!(!harness.hackatime.requested_ends.lock().unwrap().is_empty());
+
+        block_on(logout_from_state(&harness.state)).expect("logout");
+        *scheduler.last_heartbeat.lock().unwrap() = None;
+        assert!(block_on(tick(&harness.state)).heartbeat.is_none());
+    }
+
+    #[test]
+    fn wake_is_remembered_and_skips_backoff() {
+        let scheduler = Scheduler::default();
+        scheduler.record_refresh(Err(&FlavortimeError::Network("offline".to_string())));
+        assert!(!scheduler.refresh_due());
+
+        scheduler.wake();
+        assert!(scheduler.refresh_due());
+        assert_eq!(failures(&scheduler), 1);
+        assert!(block_on(scheduler.wait_for_wake(Duration::from_secs(5))));
+        assert!(!block_on(scheduler.wait_for_wake(NO_WAKE)));
+    }
+}
@@ -0,0 +1,35 @@
+use super::commands::HackatimeData;
+use crate::error::FlavortimeError;
+use tokio::sync::watch;
+
+pub type SnapshotUpdate = Option<Result<HackatimeData, FlavortimeError>>;
+
+pub struct SnapshotProvider {
+    sender: watch::Sender<SnapshotUpdate>,
+}
+
+impl Default for SnapshotProvider {
+    fn default() -> Self {
+        Self {
+            sender: watch::Sender::new(None),
+        }
+    }
+}
+
+impl SnapshotProvider {
+    pub fn subscribe(&self) -> watch::Receiver<SnapshotUpdate> {
+        self.sender.subscribe()
+    }
+
+    pub fn latest(&self) -> Option<HackatimeData> {
+        self.sender.borrow().clone().and_then(Result::ok)
+    }
+
+    pub fn publish(&self, update: Result<HackatimeData, FlavortimeError>) {
+        self.sender.send_replace(Some(update));
+    }
+
+    pub fn clear(&self) {
+        self.sender.send_replace(None);
+    }
+}
@@ -1,5 +1,15 @@
-use crate::data::config::Config;
-use crate::services::discord::DiscordPresenceManager;
+use super::commands::unix_now_secs;
+use super::scheduler::Scheduler;
+use super::snapshot::SnapshotProvider;
+use crate::data::{
+    cache::SnapshotCache,
+    config::Config,
+    history::History,
+    runtime::runtime,
+    sessions::{self, ActiveSession},
+    stats::StatsCache,
+};
+use crate::services::{api::Services, discord::DiscordPresenceManager, http::HttpClient};
 use std::sync::Mutex;
 
 pub struct AppState {
@@ -7,29 +17,66 @@ pub struct AppState {
     pub discord: Mutex<Option<DiscordPresenceManager>>,
     pub flavortime_session_id: Mutex<Option<String>>,
     pub last_sharing_tick: Mutex<Option<u64>>,
+    pub snapshot_cache: Mutex<Option<SnapshotCache>>,
+    pub history: Mutex<Option<History>>,
+    pub session_log: Mutex<Option<ActiveSession>>,
+    pub stats: Mutex<Option<StatsCache>>,
     pub shutdown_requested: Mutex<bool>,
+    pub scheduler: Scheduler,
+    pub snapshots: SnapshotProvider,
+    pub services: Services,
 }
 
 impl AppState {
     pub fn new() -> Self {
         Self::default()
     }
-}
 
-impl Default for AppState {
-    fn default() -> Self {
-        let mut config = Config::load();
-        if config.sharing_active_seconds_total != 0 {
-            config.sharing_active_seconds_total = 0;
-            let _ = config.save();
+    pub fn with_services(mut config: Config, services: Services) -> Self {
+        let now = unix_now_secs();
+        let had_session =
+            config.flavortime_session_id.is_some() || config.sharing_active_seconds_total != 0;
+        let resumed = config.resumable_session(now);
+        if resumed.is_none() && had_session {
+            if let Err(err) = config.save() {
+                log::warn!("Failed to forget expired Flavortime session: {err}");
+            }
         }
+        let session_log = resumed.as_ref().map(|_| ActiveSession::start(now));
 
         Self {
             config: Mutex::new(config),
             discord: Mutex::new(None),
-            flavortime_session_id: Mutex::new(None),
+            flavortime_session_id: Mutex::new(resumed),
             last_sharing_tick: Mutex::new(None),
+            snapshot_cache: Mutex::new(SnapshotCache::load()),
+            history: Mutex::new(History::load()),
+            session_log: Mutex::new(session_log),
+            stats: Mutex::new(StatsCache::load()),
             shutdown_requested: Mutex::new(false),
+            scheduler: Scheduler::default(),
+            snapshots: SnapshotProvider::default(),
+            services,
+        }
+    }
+}
+
+impl Default for AppState {
+    fn default() -> Self {
+        let config = Config::load();
+        if let Err(err) = sessions::prune() {
+            log::warn!("Failed to prune session log: {err}");
+        }
+
+        let http = HttpClient::new(runtime().http_proxy.as_deref());
+        let services = Services::from_runtime(&http);
+        if let Err(err) = services.select_tracking(
+            &config.tracking_backend,
+            config.wakatime_base_url.as_deref(),
+            config.wakatime_api_key.as_deref(),
+        ) {
+            log::warn!("Falling back to Hackatime tracking: {err}");
         }
+        Self::with_services(config, services)
     }
 }
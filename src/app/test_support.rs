@@ -0,0 +1,225 @@
+use super::commands::login_with_flavortown_api_key_from_state;
+use super::state::AppState;
+use crate::data::config::{set_test_data_dir, Config, Referral};
+use crate::error::FlavortimeError;
+use crate::services::api::{
+    ApiFuture, FlavortownApi, HackatimeApi, ReferralApi, Services, TrackingSelector,
+};
+use crate::services::flavortown::{
+    CloseOutcome, FlavortownUser, HeartbeatOutcome, SessionMetadata,
+};
+use crate::services::hackatime::{CodingBlock, ProjectDetails};
+use crate::services::http::HttpClient;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use tauri::async_runtime::block_on;
+use tempfile::TempDir;
+
+pub(crate) const API_KEY: &str = "flavortown-key";
+pub(crate) const SLACK_ID: &str = "U123";
+
+#[derive(Default)]
+pub(crate) struct FakeFlavortown {
+    pub(crate) rejected_keys: HashSet<String>,
+    pub(crate) sessions_created: Mutex<u32>,
+    pub(crate) expired_sessions: Mutex<HashSet<String>>,
+    pub(crate) expire_every_session: bool,
+    pub(crate) heartbeats: Mutex<Vec<(String, u64)>>,
+    pub(crate) closed: Mutex<Vec<(String, u64)>>,
+}
+
+impl FakeFlavortown {
+    pub(crate) fn expire(&self, session_id: &str) {
+        self.expired_sessions
+            .lock()
+            .unwrap()
+            .insert(session_id.to_string());
+    }
+
+    fn is_expired(&self, session_id: &str) -> bool {
+        self.expire_every_session || self.expired_sessions.lock().unwrap().contains(session_id)
+    }
+}
+
+impl FlavortownApi for FakeFlavortown {
+    fn current_user<'a>(&'a self, api_key: &'a str) -> ApiFuture<'a, FlavortownUser> {
+        Box::pin(async move {
+            if self.rejected_keys.contains(api_key) {
+                return Err(FlavortimeError::Unauthorized("bad key".to_string()));
+            }
+            Ok(FlavortownUser {
+                slack_id: SLACK_ID.to_string(),
+            })
+        })
+    }
+
+    fn create_session<'a>(
+        &'a self,
+        _api_key: &'a str,
+        _metadata: &'a SessionMetadata,
+    ) -> ApiFuture<'a, String> {
+        Box::pin(async move {
+            let mut created = self.sessions_created.lock().unwrap();
+            *created += 1;
+            Ok(format!("session-{created}"))
+        })
+    }
+
+    fn send_heartbeat<'a>(
+        &'a self,
+        _api_key: &'a str,
+        session_id: &'a str,
+        sharing_active_seconds_total: u64,
+        _metadata: &'a SessionMetadata,
+    ) -> ApiFuture<'a, HeartbeatOutcome> {
+        Box::pin(async move {
+            if self.is_expired(session_id) {
+                return Ok(HeartbeatOutcome::InvalidSessionId);
+            }
+            let mut heartbeats = self.heartbeats.lock().unwrap();
+            heartbeats.push((session_id.to_string(), sharing_active_seconds_total));
+            Ok(HeartbeatOutcome::ActiveUsers(7))
+        })
+    }
+
+    fn close_session<'a>(
+        &'a self,
+        _api_key: &'a str,
+        session_id: &'a str,
+        sharing_active_seconds_total: u64,
+        _metadata: &'a SessionMetadata,
+    ) -> ApiFuture<'a, CloseOutcome> {
+        Box::pin(async move {
+            let mut closed = self.closed.lock().unwrap();
+            closed.push((session_id.to_string(), sharing_active_seconds_total));
+            Ok(CloseOutcome::ActiveUsers(6))
+        })
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct FakeHackatime {
+    pub(crate) failure: Mutex<Option<FlavortimeError>>,
+    pub(crate) requested_ends: Mutex<Vec<String>>,
+    pub(crate) blocks: Mutex<Option<Vec<CodingBlock>>>,
+}
+
+impl FakeHackatime {
+    pub(crate) fn fail_with(&self, err: Option<FlavortimeError>) {
+        *self.failure.lock().unwrap() = err;
+    }
+}
+
+impl HackatimeApi for FakeHackatime {
+    fn user_projects_details<'a>(
+        &'a self,
+        _username: &'a str,
+        _start_date: Option<&'a str>,
+        end_date: Option<&'a str>,
+    ) -> ApiFuture<'a, Vec<ProjectDetails>> {
+        Box::pin(async move {
+            self.requested_ends
+                .lock()
+                .unwrap()
+                .extend(end_date.map(str::to_string));
+            if let Some(err) = self.failure.lock().unwrap().clone() {
+                return Err(err);
+            }
+            Ok(vec![
+                ProjectDetails {
+                    name: "flavortime".to_string(),
+                    total_seconds: 3600.0,
+                    last_heartbeat: None,
+                    most_recent_heartbeat: Some(chrono::Utc::now().to_rfc3339()),
+                    languages: vec!["Rust".to_string(), "TypeScript".to_string()],
+                },
+                ProjectDetails {
+                    name: "homework".to_string(),
+                    total_seconds: 1800.0,
+                    last_heartbeat: None,
+                    most_recent_heartbeat: Some(
+                        (chrono::Utc::now() - chrono::Duration::hours(2)).to_rfc3339(),
+                    ),
+                    languages: Vec::new(),
+                },
+            ])
+        })
+    }
+
+    fn coding_blocks<'a>(
+        &'a self,
+        _start_date: &'a str,
+        _end_date: &'a str,
+    ) -> ApiFuture<'a, Option<Vec<CodingBlock>>> {
+        Box::pin(async move { Ok(self.blocks.lock().unwrap().clone()) })
+    }
+}
+
+pub(crate) struct FakeReferrals;
+
+impl ReferralApi for FakeReferrals {
+    fn fetch_codes<'a>(&'a self, _slack_id: &'a str) -> ApiFuture<'a, Vec<Referral>> {
+        Box::pin(async {
+            Ok(vec![
+                Referral {
+                    code: "GENERATED".to_string(),
+                    code_type: "generated".to_string(),
+                },
+                Referral {
+                    code: "CUSTOM".to_string(),
+                    code_type: "custom".to_string(),
+                },
+            ])
+        })
+    }
+}
+
+pub(crate) struct Harness {
+    pub(crate) state: AppState,
+    pub(crate) flavortown: Arc<FakeFlavortown>,
+    pub(crate) hackatime: Arc<FakeHackatime>,
+    pub(crate) dir: TempDir,
+}
+
+pub(crate) fn harness_with(flavortown: FakeFlavortown) -> Harness {
+    let dir = tempfile::tempdir().expect("create temp dir");
+    set_test_data_dir(dir.path());
+
+    let flavortown = Arc::new(flavortown);
+    let hackatime = Arc::new(FakeHackatime::default());
+    let services = Services {
+        http: HttpClient::new(None),
+        hackatime: Arc::new(TrackingSelector::new(hackatime.clone())),
+        referrals: Arc::new(FakeReferrals),
+        flavortown: flavortown.clone(),
+    };
+    let config = Config {
+        app_enabled: false,
+        ..Config::default()
+    };
+
+    Harness {
+        state: AppState::with_services(config, services),
+        flavortown,
+        hackatime,
+        dir,
+    }
+}
+
+pub(crate) fn harness() -> Harness {
+    harness_with(FakeFlavortown::default())
+}
+
+pub(crate) fn logged_in() -> Harness {
+    let harness = harness();
+    block_on(login_with_flavortown_api_key_from_state(
+        &harness.state,
+        API_KEY,
+    ))
+    .expect("login");
+    harness
+}
+
+pub(crate) fn session_id(state: &AppState) -> Option<String> {
+    state.flavortime_session_id.lock().unwrap().clone()
+}
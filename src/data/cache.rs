@@ -0,0 +1,104 @@
+use super::config::data_dir;
+use super::storage::{write_atomic, DirLock};
+use crate::error::FlavortimeError;
+use crate::services::hackatime::ProjectDetails;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SnapshotCache {
+    pub slack_id: String,
+    pub window_start: u64,
+    pub fetched_at: u64,
+    pub projects: Vec<ProjectDetails>,
+}
+
+impl SnapshotCache {
+    pub fn new(
+        slack_id: &str,
+        projects: Vec<ProjectDetails>,
+        window_start: u64,
+        fetched_at: u64,
+    ) -> Self {
+        Self {
+            slack_id: slack_id.to_string(),
+            window_start,
+            fetched_at,
+            projects,
+        }
+    }
+
+    pub fn load() -> Option<Self> {
+        let raw = fs::read_to_string(Self::path()).ok()?;
+        serde_json::from_str(&raw)
+            .map_err(|err| log::warn!("Ignoring unreadable snapshot cache: {err}"))
+            .ok()
+    }
+
+    pub fn save(&self) -> Result<(), FlavortimeError> {
+        let raw = serde_json::to_string(self)?;
+        let _lock = DirLock::acquire(&data_dir())?;
+        write_atomic(&Self::path(), raw.as_bytes())
+    }
+
+    pub fn clear() {
+        if let Err(err) = fs::remove_file(Self::path()) {
+            if err.kind() != std::io::ErrorKind::NotFound {
+                log::warn!("Failed to remove snapshot cache: {err}");
+            }
+        }
+    }
+
+    pub fn usable_for(&self, slack_id: &str, max_age_secs: u64, now: u64) -> bool {
+        self.slack_id == slack_id && now.saturating_sub(self.fetched_at) <= max_age_secs
+    }
+
+    fn path() -> PathBuf {
+        data_dir().join("snapshot_cache.json")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::config::set_test_data_dir;
+
+    fn project(name: &str, total_seconds: f64) -> ProjectDetails {
+        ProjectDetails {
+            name: name.to_string(),
+            total_seconds,
+            last_heartbeat: None,
+            most_recent_heartbeat: Some("2026-01-01T12:00:00Z".to_string()),
+            languages: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn roundtrips_and_clears() {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        set_test_data_dir(dir.path());
+
+        assert!(SnapshotCache::load().is_none());
+        SnapshotCache::new("U1", vec![project("flavortime", 90.0)], 0, 1_000)
+            .save()
+            .expect("save cache");
+
+        let loaded = SnapshotCache::load().expect("load cache");
+        assert_eq!(loaded.slack_id, "U1");
+        assert_eq!(loaded.fetched_at, 1_000);
+        assert_eq!(loaded.projects[0].name, "flavortime");
+
+        SnapshotCache::clear();
+        assert!(SnapshotCache::load().is_none());
+    }
+
+    #[test]
+    fn usable_only_for_same_account_within_max_age() {
+        let cache = SnapshotCache::new("U1", Vec::new(), 0, 1_000);
+        assert!(cache.usable_for("U1", 600, 1_600));
+        assert!(!cache.usable_for("U1", 600, 1_601));
+        assert!(!cache.usable_for("U2", 600, 1_000));
+        assert!(!cache.usable_for("U1", 0, 1_001));
+    }
+}
@@ -1,3 +1,9 @@
+use super::locale::rich_presence_text;
+use super::migrations::{migrate_config, CONFIG_SCHEMA_VERSION};
+use super::secrets::{secret_store, FLAVORTOWN_API_KEY, WAKATIME_API_KEY};
+use super::storage::{write_atomic, DirLock};
+use crate::error::FlavortimeError;
+use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -10,6 +16,114 @@ pub enum Mode {
     Adult,
 }
 
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum TrackingBackend {
+    #[default]
+    Hackatime,
+    WakaTime,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PrivacyMatch {
+    #[default]
+    Exact,
+    Glob,
+    Regex,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum PrivacyAction {
+    Hide,
+    Alias { alias: String },
+    Secret,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PrivacyRule {
+    pub pattern: String,
+    #[serde(default)]
+    pub matcher: PrivacyMatch,
+    #[serde(flatten)]
+    pub action: PrivacyAction,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum WindowMode {
+    #[default]
+    Rolling24h,
+    LocalDay,
+    Custom {
+        utc_offset_minutes: i32,
+        day_start_hour: u32,
+    },
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ProjectHoursSource {
+    #[default]
+    Project,
+    Total,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct PresenceTemplate {
+    pub details: String,
+    pub state: String,
+    pub state_idle: String,
+    pub large_text: String,
+    pub small_text: String,
+    pub button_label: String,
+}
+
+impl Default for PresenceTemplate {
+    fn default() -> Self {
+        let text = rich_presence_text();
+        Self {
+            details: text.status_tagline.clone(),
+            state: format!(
+                "{}{{project}}[ ({{project_hours}} spent)]",
+                text.details_project_prefix
+            ),
+            state_idle: text.details_idle.clone(),
+            large_text: text.brand_label.clone(),
+            small_text: format!("[{{window}}: {{today}}{}]", text.time_logged_suffix),
+            button_label: text.referral_button.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct PresenceProfile {
+    pub name: String,
+    pub show_referral_code: bool,
+    pub show_time_tracking: bool,
+    pub selected_referral_code: Option<String>,
+    pub custom_referral_code: Option<String>,
+    pub presence_template: PresenceTemplate,
+}
+
+impl Default for PresenceProfile {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            show_referral_code: true,
+            show_time_tracking: true,
+            selected_referral_code: None,
+            custom_referral_code: None,
+            presence_template: PresenceTemplate::default(),
+        }
+    }
+}
+
+pub const PROFILE_NAME_LIMIT: usize = 32;
+pub const SESSION_RESUME_SECS: u64 = 15 * 60;
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Referral {
     pub code: String,
@@ -17,9 +131,12 @@ pub struct Referral {
 }
 
 #[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Config {
+    pub schema_version: u32,
     pub auth_mode: Mode,
     pub slack_id: Option<String>,
+    #[serde(skip_serializing)]
     pub flavortown_api_key: Option<String>,
     pub available_referral_codes: Vec<Referral>,
     pub selected_referral_code: Option<String>,
@@ -29,11 +146,27 @@ pub struct Config {
     pub launch_at_startup: bool,
     pub app_enabled: bool,
     pub sharing_active_seconds_total: u64,
+    pub flavortime_session_id: Option<String>,
+    pub flavortime_session_seen_at: u64,
+    pub tracking_backend: TrackingBackend,
+    pub wakatime_base_url: Option<String>,
+    #[serde(skip)]
+    pub wakatime_api_key: Option<String>,
+    pub max_snapshot_staleness_minutes: u64,
+    pub window_mode: WindowMode,
+    pub presence_project_hours: ProjectHoursSource,
+    pub presence_template: PresenceTemplate,
+    pub presence_profiles: Vec<PresenceProfile>,
+    pub active_presence_profile: Option<String>,
+    pub privacy_rules: Vec<PrivacyRule>,
+    pub daily_goal_minutes: u64,
+    pub history_days: u32,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
+            schema_version: CONFIG_SCHEMA_VERSION,
             auth_mode: Mode::None,
             slack_id: None,
             flavortown_api_key: None,
@@ -45,47 +178,145 @@ impl Default for Config {
             launch_at_startup: false,
             app_enabled: true,
             sharing_active_seconds_total: 0,
+            flavortime_session_id: None,
+            flavortime_session_seen_at: 0,
+            tracking_backend: TrackingBackend::Hackatime,
+            wakatime_base_url: None,
+            wakatime_api_key: None,
+            max_snapshot_staleness_minutes: 30,
+            window_mode: WindowMode::Rolling24h,
+            presence_project_hours: ProjectHoursSource::Project,
+            presence_template: PresenceTemplate::default(),
+            presence_profiles: Vec::new(),
+            active_presence_profile: None,
+            privacy_rules: Vec::new(),
+            daily_goal_minutes: 60,
+            history_days: 30,
         }
     }
 }
 
 impl Config {
     pub fn load() -> Self {
-        for path in [Self::path(), Self::backup_path()] {
-            if let Some(config) = Self::load_from_path(&path) {
-                return config;
-            }
+        let mut config = Self::read();
+        if config.flavortown_api_key.is_some() {
+            config.migrate_plaintext_api_key();
+        } else {
+            config.flavortown_api_key =
+                secret_store()
+                    .get(FLAVORTOWN_API_KEY)
+                    .unwrap_or_else(|err| {
+                        log::warn!("Failed to read Flavortown API key from secret store: {err}");
+                        None
+                    });
         }
 
-        Self::default()
+        if config.tracking_backend == TrackingBackend::WakaTime {
+            config.wakatime_api_key = secret_store().get(WAKATIME_API_KEY).unwrap_or_else(|err| {
+                log::warn!("Failed to read WakaTime API key from secret store: {err}");
+                None
+            });
+        }
+
+        config
     }
 
-    pub fn save(&self) -> Result<(), String> {
-        let path = Self::path();
-        let backup = Self::backup_path();
-        if let Some(parent) = path.parent() {
-            fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+    pub fn set_flavortown_api_key(
+        &mut self,
+        api_key: Option<String>,
+    ) -> Result<(), FlavortimeError> {
+        match api_key.as_deref() {
+            Some(value) => secret_store().set(FLAVORTOWN_API_KEY, value)?,
+            None => secret_store().delete(FLAVORTOWN_API_KEY)?,
         }
+        self.flavortown_api_key = api_key;
+        Ok(())
+    }
 
-        if path.exists() {
-            let _ = fs::copy(&path, &backup);
+    pub fn set_wakatime_api_key(&mut self, api_key: Option<String>) -> Result<(), FlavortimeError> {
+        match api_key.as_deref() {
+            Some(value) => secret_store().set(WAKATIME_API_KEY, value)?,
+            None => secret_store().delete(WAKATIME_API_KEY)?,
         }
+        self.wakatime_api_key = api_key;
+        Ok(())
+    }
 
-        let raw = serde_json::to_string_pretty(self).map_err(|err| err.to_string())?;
-        fs::write(&path, &raw).map_err(|err| err.to_string())?;
-        let _ = fs::write(&backup, &raw);
+    pub fn save(&self) -> Result<(), FlavortimeError> {
+        let raw = serde_json::to_string_pretty(self)
+            .map_err(|err| FlavortimeError::Config(err.to_string()))?;
+        let _lock = DirLock::acquire(&data_dir())?;
+        write_atomic(&Self::path(), raw.as_bytes())?;
+        if let Err(err) = write_atomic(&Self::backup_path(), raw.as_bytes()) {
+            log::warn!("Failed to write config backup: {err}");
+        }
         Ok(())
     }
 
     pub fn reset(&mut self) {
+        if let Err(err) = self.set_flavortown_api_key(None) {
+            log::warn!("Failed to remove Flavortown API key from secret store: {err}");
+        }
+        if let Err(err) = self.set_wakatime_api_key(None) {
+            log::warn!("Failed to remove WakaTime API key from secret store: {err}");
+        }
         *self = Self::default();
     }
 
+    pub fn resumable_session(&mut self, now: u64) -> Option<String> {
+        let resumable = self
+            .flavortime_session_id
+            .clone()
+            .filter(|_| matches!(self.auth_mode, Mode::Hackatime))
+            .filter(|_| now.saturating_sub(self.flavortime_session_seen_at) <= SESSION_RESUME_SECS);
+        if resumable.is_none() {
+            self.flavortime_session_id = None;
+            self.flavortime_session_seen_at = 0;
+            self.sharing_active_seconds_total = 0;
+        }
+        resumable
+    }
+
+    fn migrate_plaintext_api_key(&mut self) {
+        let Some(api_key) = self.flavortown_api_key.clone() else {
+            return;
+        };
+
+        match secret_store().set(FLAVORTOWN_API_KEY, &api_key) {
+            Ok(()) => {
+                if let Err(err) = self.save() {
+                    log::warn!("Failed to rewrite config after moving API key: {err}");
+                }
+            }
+            Err(err) => log::warn!("Failed to move Flavortown API key to secret store: {err}"),
+        }
+    }
+
+    fn read() -> Self {
+        let loaded = {
+            let _lock = DirLock::acquire(&data_dir())
+                .map_err(|err| log::warn!("Failed to lock config directory: {err}"))
+                .ok();
+            [Self::path(), Self::backup_path()]
+                .iter()
+                .enumerate()
+                .find_map(|(index, path)| {
+                    let (config, migrated) = Self::load_from_path(path)?;
+                    Some((config, migrated || index > 0))
+                })
+        };
+
+        let (config, rewrite) = loaded.unwrap_or_default();
+        if rewrite {
+            if let Err(err) = config.save() {
+                log::warn!("Failed to save migrated or recovered config: {err}");
+            }
+        }
+        config
+    }
+
     fn path() -> PathBuf {
-        dirs::data_dir()
-            .unwrap_or_else(|| PathBuf::from("."))
-            .join("flavortime")
-            .join("config.json")
+        data_dir().join("config.json")
     }
 
     fn backup_path() -> PathBuf {
@@ -94,9 +325,62 @@ impl Config {
         path
     }
 
-    fn load_from_path(path: &Path) -> Option<Self> {
+    fn load_from_path(path: &Path) -> Option<(Self, bool)> {
         let raw = fs::read_to_string(path).ok()?;
-        serde_json::from_str(&raw).ok()
+        match Self::parse(&raw) {
+            Ok((config, original_version)) => {
+                if original_version > CONFIG_SCHEMA_VERSION {
+                    Self::preserve_newer(path, original_version);
+                }
+                Some((config, original_version < CONFIG_SCHEMA_VERSION))
+            }
+            Err(err) => {
+                log::warn!("Failed to load config from {}: {err}", path.display());
+                Self::quarantine(path);
+                None
+            }
+        }
+    }
+
+    fn parse(raw: &str) -> Result<(Self, u32), FlavortimeError> {
+        let mut value = serde_json::from_str::<serde_json::Value>(raw)?;
+        let original_version = migrate_config(&mut value)?;
+        let mut config = serde_json::from_value::<Self>(value)?;
+        config.schema_version = config.schema_version.max(CONFIG_SCHEMA_VERSION);
+        Ok((config, original_version))
+    }
+
+    fn quarantine(path: &Path) {
+        let stem = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or("config");
+        let quarantined = path.with_file_name(format!(
+            "{stem}.corrupt-{}.json",
+            Utc::now().format("%Y%m%dT%H%M%S")
+        ));
+        match fs::rename(path, &quarantined) {
+            Ok(_) => log::warn!("Quarantined unreadable config at {}", quarantined.display()),
+            Err(err) => log::warn!("Failed to quarantine {}: {err}", path.display()),
+        }
+    }
+
+    // Saving drops fields this build does not know about, so keep the file a
+    // newer Flavortime wrote before the first save. The first copy is the
+    // complete one and is never replaced.
+    fn preserve_newer(path: &Path, version: u32) {
+        let preserved = path.with_file_name(format!("config.schema-v{version}.json"));
+        if preserved.exists() {
+            return;
+        }
+        match fs::copy(path, &preserved) {
+            Ok(_) => log::warn!(
+                "Config schema version {version} is newer than supported version \
+                 {CONFIG_SCHEMA_VERSION}; kept a copy at {}",
+                preserved.display()
+            ),
+            Err(err) => log::warn!("Failed to keep a copy of {}: {err}", path.display()),
+        }
     }
 
     fn preferred_code(&self) -> Option<String> {
@@ -129,6 +413,101 @@ impl Config {
         false
     }
 
+    pub fn create_profile(&mut self, name: &str) -> Result<(), FlavortimeError> {
+        let name = self.available_profile_name(name, None)?;
+        self.presence_profiles
+            .push(self.profile_from_current(&name));
+        self.active_presence_profile = Some(name);
+        Ok(())
+    }
+
+    pub fn rename_profile(&mut self, name: &str, new_name: &str) -> Result<(), FlavortimeError> {
+        let new_name = self.available_profile_name(new_name, Some(name))?;
+        let profile = self.profile_mut(name)?;
+        profile.name = new_name.clone();
+        if self.active_presence_profile.as_deref() == Some(name) {
+            self.active_presence_profile = Some(new_name);
+        }
+        Ok(())
+    }
+
+    pub fn delete_profile(&mut self, name: &str) -> Result<(), FlavortimeError> {
+        self.profile_mut(name)?;
+        self.presence_profiles
+            .retain(|profile| profile.name != name);
+        if self.active_presence_profile.as_deref() == Some(name) {
+            self.active_presence_profile = None;
+        }
+        Ok(())
+    }
+
+    pub fn activate_profile(&mut self, name: &str) -> Result<(), FlavortimeError> {
+        let profile = self.profile_mut(name)?.clone();
+        self.show_referral_code = profile.show_referral_code;
+        self.show_time_tracking = profile.show_time_tracking;
+        self.selected_referral_code = profile.selected_referral_code;
+        self.custom_referral_code = profile.custom_referral_code;
+        self.presence_template = profile.presence_template;
+        self.active_presence_profile = Some(profile.name);
+        Ok(())
+    }
+
+    pub fn capture_active_profile(&mut self) {
+        let Some(name) = self.active_presence_profile.clone() else {
+            return;
+        };
+        let current = self.profile_from_current(&name);
+        match self.profile_mut(&name) {
+            Ok(profile) => *profile = current,
+            Err(_) => self.active_presence_profile = None,
+        }
+    }
+
+    fn profile_from_current(&self, name: &str) -> PresenceProfile {
+        PresenceProfile {
+            name: name.to_string(),
+            show_referral_code: self.show_referral_code,
+            show_time_tracking: self.show_time_tracking,
+            selected_referral_code: self.selected_referral_code.clone(),
+            custom_referral_code: self.custom_referral_code.clone(),
+            presence_template: self.presence_template.clone(),
+        }
+    }
+
+    fn profile_mut(&mut self, name: &str) -> Result<&mut PresenceProfile, FlavortimeError> {
+        self.presence_profiles
+            .iter_mut()
+            .find(|profile| profile.name == name)
+            .ok_or_else(|| FlavortimeError::NotFound(format!("No profile named {name}")))
+    }
+
+    fn available_profile_name(
+        &self,
+        name: &str,
+        replacing: Option<&str>,
+    ) -> Result<String, FlavortimeError> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(FlavortimeError::InvalidInput(
+                "Profile name is required".to_string(),
+            ));
+        }
+        if name.chars().count() > PROFILE_NAME_LIMIT {
+            return Err(FlavortimeError::InvalidInput(format!(
+                "Profile names can be at most {PROFILE_NAME_LIMIT} characters"
+            )));
+        }
+        let taken = self.presence_profiles.iter().any(|profile| {
+            Some(profile.name.as_str()) != replacing && profile.name.eq_ignore_ascii_case(name)
+        });
+        if taken {
+            return Err(FlavortimeError::InvalidInput(format!(
+                "A profile named {name} already exists"
+            )));
+        }
+        Ok(name.to_string())
+    }
+
     pub fn display_code(&self) -> Option<String> {
         if !self.show_referral_code {
             return None;
@@ -143,3 +522,194 @@ impl Config {
             .or_else(|| self.preferred_code())
     }
 }
+
+#[cfg(test)]
+thread_local! {
+    static DATA_DIR_OVERRIDE: std::cell::RefCell<Option<PathBuf>> =
+        const { std::cell::RefCell::new(None) };
+}
+
+#[cfg(test)]
+pub(crate) fn set_test_data_dir(dir: &Path) {
+    DATA_DIR_OVERRIDE.with(|current| *current.borrow_mut() = Some(dir.to_path_buf()));
+}
+
+pub fn data_dir() -> PathBuf {
+    #[cfg(test)]
+    if let Some(dir) = DATA_DIR_OVERRIDE.with(|dir| dir.borrow().clone()) {
+        return dir;
+    }
+
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("flavortime")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn temp_data_dir() -> TempDir {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        set_test_data_dir(dir.path());
+        dir
+    }
+
+    fn sample() -> Config {
+        Config {
+            slack_id: Some("U123".to_string()),
+            custom_referral_code: Some("FLAVOR".to_string()),
+            sharing_active_seconds_total: 42,
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn path_follows_data_dir_override() {
+        let dir = temp_data_dir();
+        assert_eq!(Config::path(), dir.path().join("config.json"));
+        assert_eq!(Config::backup_path(), dir.path().join("config.backup.json"));
+    }
+
+    #[test]
+    fn save_then_read_round_trips() {
+        let _dir = temp_data_dir();
+        sample().save().expect("save config");
+
+        let loaded = Config::read();
+        assert_eq!(loaded.slack_id.as_deref(), Some("U123"));
+        assert_eq!(loaded.custom_referral_code.as_deref(), Some("FLAVOR"));
+        assert_eq!(loaded.sharing_active_seconds_total, 42);
+        assert_eq!(loaded.schema_version, CONFIG_SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn save_leaves_no_temp_files_behind() {
+        let dir = temp_data_dir();
+        sample().save().expect("save config");
+        sample().save().expect("save config again");
+
+        let leftovers: Vec<_> = fs::read_dir(dir.path())
+            .expect("read temp dir")
+            .filter_map(Result::ok)
+            .filter(|entry| entry.file_name().to_string_lossy().ends_with(".tmp"))
+            .collect();
+        assert!(leftovers.is_empty());
+    }
+
+    #[test]
+    fn save_does_not_write_api_key() {
+        let _dir = temp_data_dir();
+        let config = Config {
+            flavortown_api_key: Some("secret".to_string()),
+            ..sample()
+        };
+        config.save().expect("save config");
+
+        let raw = fs::read_to_string(Config::path()).expect("read config");
+        assert!(!raw.contains("secret"));
+    }
+
+    #[test]
+    fn corrupt_config_recovers_from_backup() {
+        let dir = temp_data_dir();
+        sample().save().expect("save config");
+        fs::write(Config::path(), "{ not json").expect("corrupt config");
+
+        let quarantined = || {
+            fs::read_dir(dir.path())
+                .expect("read temp dir")
+                .filter_map(Result::ok)
+                .filter(|entry| entry.file_name().to_string_lossy().contains(".corrupt-"))
+                .count()
+        };
+
+        let loaded = Config::read();
+        assert_eq!(loaded.slack_id.as_deref(), Some("U123"));
+        assert_eq!(quarantined(), 1);
+
+        let raw = fs::read_to_string(Config::path()).expect("recovered config");
+        assert!(raw.contains("\"U123\""));
+        let loaded = Config::read();
+        assert_eq!(loaded.slack_id.as_deref(), Some("U123"));
+        assert_eq!(quarantined(), 1);
+    }
+
+    #[test]
+    fn newer_config_is_kept_before_it_is_rewritten() {
+        let dir = temp_data_dir();
+        let newer = r#"{"schema_version":99,"slack_id":"U7","future_field":true}"#;
+        fs::write(Config::path(), newer).expect("write newer config");
+
+        let loaded = Config::read();
+        assert_eq!(loaded.slack_id.as_deref(), Some("U7"));
+        loaded.save().expect("save config");
+        Config::read().save().expect("save config again");
+
+        let preserved = dir.path().join("config.schema-v99.json");
+        assert_eq!(fs::read_to_string(preserved).expect("preserved"), newer);
+    }
+
+    #[test]
+    fn missing_files_fall_back_to_default() {
+        let _dir = temp_data_dir();
+        let loaded = Config::read();
+        assert!(loaded.slack_id.is_none());
+        assert!(loaded.app_enabled);
+    }
+
+    #[test]
+    fn legacy_config_is_migrated_and_rewritten() {
+        let _dir = temp_data_dir();
+        fs::write(
+            Config::path(),
+            r#"{"auth_mode":"Hackatime","slack_id":"U9","custom_referral_code":null}"#,
+        )
+        .expect("write legacy config");
+
+        let loaded = Config::read();
+        assert!(loaded.auth_mode == Mode::Hackatime);
+        assert_eq!(loaded.slack_id.as_deref(), Some("U9"));
+        assert!(loaded.show_time_tracking);
+
+        let raw = fs::read_to_string(Config::path()).expect("read config");
+        assert!(raw.contains("\"schema_version\""));
+    }
+
+    #[test]
+    fn profiles_capture_and_restore_presence_settings() {
+        let mut config = sample();
+        config
+            .create_profile(" streaming ")
+            .expect("create streaming");
+        assert_eq!(config.active_presence_profile.as_deref(), Some("streaming"));
+
+        config.create_profile("school").expect("create school");
+        config.show_referral_code = false;
+        config.custom_referral_code = None;
+        config.capture_active_profile();
+
+        config
+            .activate_profile("streaming")
+            .expect("activate streaming");
+        assert!(config.show_referral_code);
+        assert_eq!(config.custom_referral_code.as_deref(), Some("FLAVOR"));
+
+        assert!(config.create_profile("School").is_err());
+        assert!(config.rename_profile("school", "Streaming").is_err());
+        config.rename_profile("streaming", "live").expect("rename");
+        assert_eq!(config.active_presence_profile.as_deref(), Some("live"));
+
+        config.delete_profile("live").expect("delete");
+        assert!(config.active_presence_profile.is_none());
+        assert!(matches!(
+            config.activate_profile("live"),
+            Err(FlavortimeError::NotFound(_))
+        ));
+
+        config.activate_profile("school").expect("activate school");
+        assert!(!config.show_referral_code);
+        assert!(config.display_code().is_none());
+    }
+}
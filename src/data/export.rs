@@ -0,0 +1,341 @@
+use super::history::parse_date;
+use super::stats::{project_totals, DayProjects, ProjectTotal};
+use super::storage::write_atomic;
+use crate::error::FlavortimeError;
+use crate::services::hackatime::CodingBlock;
+use crate::services::presence::fmt_hours;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+const ICS_LINE_LIMIT: usize = 75;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    Csv,
+    Json,
+    Ics,
+}
+
+#[derive(Serialize)]
+struct ExportDocument<'a> {
+    start_date: &'a str,
+    end_date: &'a str,
+    total_seconds: f64,
+    projects: Vec<ProjectTotal>,
+    days: &'a [DayProjects],
+}
+
+impl ExportFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Json => "json",
+            Self::Ics => "ics",
+        }
+    }
+
+    pub fn render(
+        self,
+        start_date: &str,
+        end_date: &str,
+        days: &[DayProjects],
+        blocks: Option<&[CodingBlock]>,
+    ) -> Result<String, FlavortimeError> {
+        match self {
+            Self::Csv => Ok(render_csv(days)),
+            Self::Json => {
+                let projects = project_totals(days);
+                let document = ExportDocument {
+                    start_date,
+                    end_date,
+                    total_seconds: projects.iter().map(|project| project.seconds).sum(),
+                    projects,
+                    days,
+                };
+                Ok(serde_json::to_string_pretty(&document)?)
+            }
+            Self::Ics => Ok(render_ics(
+                days,
+                blocks,
+                &Utc::now().format("%Y%m%dT%H%M%SZ").to_string(),
+            )),
+        }
+    }
+}
+
+pub fn filter_projects(days: Vec<DayProjects>, projects: &[String]) -> Vec<DayProjects> {
+    let wanted = wanted_projects(projects);
+    if wanted.is_empty() {
+        return days;
+    }
+
+    days.into_iter()
+        .map(|mut day| {
+            day.projects
+                .retain(|project| wanted.contains(&project.name.to_lowercase()));
+            day
+        })
+        .collect()
+}
+
+pub fn filter_blocks(mut blocks: Vec<CodingBlock>, projects: &[String]) -> Vec<CodingBlock> {
+    let wanted = wanted_projects(projects);
+    if !wanted.is_empty() {
+        blocks.retain(|block| wanted.contains(&block.project.to_lowercase()));
+    }
+    blocks
+}
+
+fn wanted_projects(projects: &[String]) -> Vec<String> {
+    projects
+        .iter()
+        .map(|name| name.trim().to_lowercase())
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+pub fn export_path(path: &str, format: ExportFormat) -> Result<PathBuf, FlavortimeError> {
+    let path = Path::new(path.trim());
+    if !path.is_absolute() {
+        return Err(FlavortimeError::InvalidInput(
+            "Export path must be absolute".to_string(),
+        ));
+    }
+    if !path.parent().is_some_and(Path::is_dir) {
+        return Err(FlavortimeError::InvalidInput(
+            "Export folder does not exist".to_string(),
+        ));
+    }
+    if path.is_dir() {
+        return Err(FlavortimeError::InvalidInput(
+            "Export path is a folder".to_string(),
+        ));
+    }
+
+    let mut path = path.to_path_buf();
+    if path.extension().is_none() {
+        path.set_extension(format.extension());
+    }
+    Ok(path)
+}
+
+pub fn write(path: &Path, contents: &str) -> Result<(), FlavortimeError> {
+    write_atomic(path, contents.as_bytes())
+}
+
+fn render_csv(days: &[DayProjects]) -> String {
+    let mut out = String::from("date,project,seconds,hours\n");
+    for day in days {
+        for project in &day.projects {
+            let _ = writeln!(
+                out,
+                "{},{},{:.0},{:.2}",
+                day.date,
+                csv_field(&project.name),
+                project.seconds,
+                project.seconds / 3600.0
+            );
+        }
+    }
+    out
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+// Backends with timing data get one event per coding block. Hackatime only
+// reports totals, so its days fall back to one all-day event per project.
+fn render_ics(days: &[DayProjects], blocks: Option<&[CodingBlock]>, stamp: &str) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//Hack Club//Flavortime//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+    ];
+
+    match blocks {
+        Some(blocks) => {
+            for block in blocks {
+                let (Some(start), Some(end)) = (
+                    DateTime::from_timestamp(block.start_unix, 0),
+                    DateTime::from_timestamp(block.end_unix, 0),
+                ) else {
+                    continue;
+                };
+                let seconds = (block.end_unix - block.start_unix) as f64;
+                lines.extend(ics_event(
+                    format!("{}-{}", block.start_unix, ics_slug(&block.project)),
+                    stamp,
+                    [
+                        format!("DTSTART:{}", start.format("%Y%m%dT%H%M%SZ")),
+                        format!("DTEND:{}", end.format("%Y%m%dT%H%M%SZ")),
+                    ],
+                    &block.project,
+                    seconds,
+                ));
+            }
+        }
+        None => {
+            for day in days {
+                let Ok(date) = parse_date(&day.date) else {
+                    continue;
+                };
+                let Some(next) = date.succ_opt() else {
+                    continue;
+                };
+                for (index, project) in day.projects.iter().enumerate() {
+                    lines.extend(ics_event(
+                        format!(
+                            "{}-{index}-{}",
+                            date.format("%Y%m%d"),
+                            ics_slug(&project.name)
+                        ),
+                        stamp,
+                        [
+                            format!("DTSTART;VALUE=DATE:{}", date.format("%Y%m%d")),
+                            format!("DTEND;VALUE=DATE:{}", next.format("%Y%m%d")),
+                        ],
+                        &project.name,
+                        project.seconds,
+                    ));
+                }
+            }
+        }
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    let mut out = String::new();
+    for line in lines {
+        fold_ics_line(&mut out, &line);
+    }
+    out
+}
+
+fn ics_event(
+    uid: String,
+    stamp: &str,
+    [dtstart, dtend]: [String; 2],
+    project: &str,
+    seconds: f64,
+) -> [String; 9] {
+    [
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:{uid}@flavortime"),
+        format!("DTSTAMP:{stamp}"),
+        dtstart,
+        dtend,
+        format!(
+            "SUMMARY:{}",
+            ics_text(&format!("{project} ({})", fmt_hours(seconds / 3600.0)))
+        ),
+        format!(
+            "DESCRIPTION:{}",
+            ics_text(&format!("{seconds:.0} seconds coding on {project}"))
+        ),
+        "TRANSP:TRANSPARENT".to_string(),
+        "END:VEVENT".to_string(),
+    ]
+}
+
+fn ics_slug(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect()
+}
+
+fn ics_text(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace(['\r', '\n'], "\\n")
+}
+
+fn fold_ics_line(out: &mut String, line: &str) {
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > ICS_LINE_LIMIT {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::stats::day_projects;
+
+    fn days() -> Vec<DayProjects> {
+        vec![
+            day_projects(
+                "2026-03-01",
+                [
+                    ("api, \"v2\"".to_string(), 5400.0),
+                    ("web".to_string(), 600.0),
+                ],
+            ),
+            day_projects("2026-03-02", [("Web".to_string(), 1800.0)]),
+        ]
+    }
+
+    #[test]
+    fn csv_quotes_fields_and_filters_projects() {
+        let csv = render_csv(&days());
+        assert_eq!(
+            csv,
+            "date,project,seconds,hours\n\
+             2026-03-01,\"api, \"\"v2\"\"\",5400,1.50\n\
+             2026-03-01,web,600,0.17\n\
+             2026-03-02,Web,1800,0.50\n"
+        );
+
+        let filtered = filter_projects(days(), &["web".to_string(), " ".to_string()]);
+        assert_eq!(filtered[0].projects.len(), 1);
+        assert_eq!(filtered[1].projects[0].name, "Web");
+        assert_eq!(filter_projects(days(), &[])[0].projects.len(), 2);
+    }
+
+    #[test]
+    fn ics_has_one_timed_event_per_coding_block() {
+        let blocks = [
+            CodingBlock {
+                project: "api".to_string(),
+                start_unix: 1_772_359_200,
+                end_unix: 1_772_364_600,
+            },
+            CodingBlock {
+                project: "web".to_string(),
+                start_unix: 1_772_406_000,
+                end_unix: 1_772_406_600,
+            },
+        ];
+        let ics = render_ics(&days(), Some(&blocks), "20260303T120000Z");
+        assert_eq!(ics.matches("BEGIN:VEVENT").count(), 2);
+        assert!(ics.contains("DTSTART:20260301T100000Z\r\nDTEND:20260301T113000Z\r\n"));
+        assert!(ics.contains("SUMMARY:api (1h 30m)\r\n"));
+        assert!(!ics.contains("VALUE=DATE"));
+    }
+
+    #[test]
+    fn ics_without_blocks_has_one_all_day_event_per_project_and_day() {
+        let ics = render_ics(&days(), None, "20260303T120000Z");
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        assert_eq!(ics.matches("BEGIN:VEVENT").count(), 3);
+        assert!(ics.contains("DTSTART;VALUE=DATE:20260301\r\nDTEND;VALUE=DATE:20260302\r\n"));
+        assert!(ics.contains("SUMMARY:api\\, \"v2\" (1h 30m)\r\n"));
+        assert!(ics.lines().all(|line| line.len() <= ICS_LINE_LIMIT));
+    }
+}
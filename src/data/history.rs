@@ -0,0 +1,231 @@
+use super::config::data_dir;
+use super::storage::{write_atomic, DirLock};
+use crate::error::FlavortimeError;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+pub fn parse_date(value: &str) -> Result<NaiveDate, FlavortimeError> {
+    NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d")
+        .map_err(|_| FlavortimeError::InvalidInput(format!("Invalid date: {value}")))
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DayTotal {
+    pub date: String,
+    pub seconds: f64,
+}
+
+#[derive(Clone, Copy, Default, Serialize)]
+pub struct Streaks {
+    pub current: u32,
+    pub longest: u32,
+    pub today_seconds: f64,
+    pub goal_met_today: bool,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct History {
+    pub slack_id: String,
+    pub day_key: String,
+    pub fetched_at: u64,
+    pub days: Vec<DayTotal>,
+}
+
+impl History {
+    pub fn new(slack_id: &str, day_key: &str) -> Self {
+        Self {
+            slack_id: slack_id.to_string(),
+            day_key: day_key.to_string(),
+            fetched_at: 0,
+            days: Vec::new(),
+        }
+    }
+
+    pub fn load() -> Option<Self> {
+        let raw = fs::read_to_string(Self::path()).ok()?;
+        serde_json::from_str(&raw)
+            .map_err(|err| log::warn!("Ignoring unreadable history: {err}"))
+            .ok()
+    }
+
+    pub fn save(&self) -> Result<(), FlavortimeError> {
+        let raw = serde_json::to_string(self)?;
+        let _lock = DirLock::acquire(&data_dir())?;
+        write_atomic(&Self::path(), raw.as_bytes())
+    }
+
+    pub fn clear() {
+        if let Err(err) = fs::remove_file(Self::path()) {
+            if err.kind() != std::io::ErrorKind::NotFound {
+                log::warn!("Failed to remove history: {err}");
+            }
+        }
+    }
+
+    pub fn matches(&self, slack_id: &str, day_key: &str) -> bool {
+        self.slack_id == slack_id && self.day_key == day_key
+    }
+
+    pub fn has(&self, date: &str) -> bool {
+        self.days.iter().any(|day| day.date == date)
+    }
+
+    pub fn record(&mut self, date: &str, seconds: f64) {
+        let seconds = seconds.max(0.0);
+        match self.days.iter_mut().find(|day| day.date == date) {
+            Some(day) => day.seconds = seconds,
+            None => {
+                self.days.push(DayTotal {
+                    date: date.to_string(),
+                    seconds,
+                });
+                self.days.sort_by(|a, b| a.date.cmp(&b.date));
+            }
+        }
+    }
+
+    pub fn retain_last(&mut self, days: usize) {
+        let excess = self.days.len().saturating_sub(days);
+        self.days.drain(..excess);
+    }
+
+    pub fn streaks(&self, goal_seconds: f64, today: &str) -> Streaks {
+        let met = |seconds: f64| seconds > 0.0 && seconds >= goal_seconds;
+        let dated: Vec<(NaiveDate, bool)> = self
+            .days
+            .iter()
+            .filter_map(|day| {
+                let date = NaiveDate::parse_from_str(&day.date, "%Y-%m-%d").ok()?;
+                Some((date, met(day.seconds)))
+            })
+            .collect();
+
+        let mut longest = 0;
+        let mut run = 0;
+        let mut previous: Option<NaiveDate> = None;
+        for (date, ok) in &dated {
+            if *ok {
+                let continues = previous.and_then(|day| day.succ_opt()) == Some(*date);
+                run = if continues { run + 1 } else { 1 };
+                previous = Some(*date);
+                longest = longest.max(run);
+            } else {
+                run = 0;
+                previous = None;
+            }
+        }
+
+        let today_seconds = self
+            .days
+            .iter()
+            .find(|day| day.date == today)
+            .map_or(0.0, |day| day.seconds);
+        let goal_met_today = met(today_seconds);
+
+        let mut current = 0;
+        let today = NaiveDate::parse_from_str(today, "%Y-%m-%d").ok();
+        let mut expected = if goal_met_today {
+            today
+        } else {
+            today.and_then(|day| day.pred_opt())
+        };
+        for (date, ok) in dated.iter().rev() {
+            let Some(want) = expected else {
+                break;
+            };
+            if *date > want {
+                continue;
+            }
+            if *date < want || !ok {
+                break;
+            }
+            current += 1;
+            expected = date.pred_opt();
+        }
+
+        Streaks {
+            current,
+            longest,
+            today_seconds,
+            goal_met_today,
+        }
+    }
+
+    fn path() -> PathBuf {
+        data_dir().join("history.json")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::config::set_test_data_dir;
+
+    fn history(days: &[(&str, f64)]) -> History {
+        let mut history = History::new("U1", "local");
+        for (date, seconds) in days {
+            history.record(date, *seconds);
+        }
+        history
+    }
+
+    #[test]
+    fn streak_survives_until_today_is_over() {
+        let history = history(&[
+            ("2026-03-01", 7200.0),
+            ("2026-03-02", 3600.0),
+            ("2026-03-03", 600.0),
+            ("2026-03-04", 4000.0),
+            ("2026-03-05", 3700.0),
+            ("2026-03-06", 0.0),
+        ]);
+
+        let streaks = history.streaks(3600.0, "2026-03-06");
+        assert_eq!(streaks.current, 2);
+        assert_eq!(streaks.longest, 2);
+        assert!(!streaks.goal_met_today);
+
+        let streaks = history.streaks(3600.0, "2026-03-07");
+        assert_eq!(streaks.current, 0);
+
+        let streaks = history.streaks(0.0, "2026-03-05");
+        assert_eq!(streaks.current, 5);
+        assert_eq!(streaks.longest, 5);
+        assert!(streaks.goal_met_today);
+    }
+
+    #[test]
+    fn gaps_break_streaks_and_old_days_are_dropped() {
+        let mut history = history(&[
+            ("2026-03-03", 3600.0),
+            ("2026-03-01", 3600.0),
+            ("2026-03-04", 3600.0),
+        ]);
+        assert_eq!(history.streaks(60.0, "2026-03-04").longest, 2);
+
+        history.record("2026-03-02", 90.0);
+        assert_eq!(history.streaks(60.0, "2026-03-04").current, 4);
+
+        history.retain_last(2);
+        assert_eq!(history.days[0].date, "2026-03-03");
+        assert!(!history.has("2026-03-01"));
+    }
+
+    #[test]
+    fn roundtrips_and_clears() {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        set_test_data_dir(dir.path());
+
+        history(&[("2026-03-01", 60.0)])
+            .save()
+            .expect("save history");
+        let loaded = History::load().expect("load history");
+        assert!(loaded.matches("U1", "local"));
+        assert!(loaded.has("2026-03-01"));
+
+        History::clear();
+        assert!(History::load().is_none());
+    }
+}
@@ -8,8 +8,10 @@ pub struct RichPresenceText {
     pub referral_button: String,
     pub referral_host: String,
     pub time_today_prefix: String,
+    pub time_rolling_prefix: String,
     pub time_logged_suffix: String,
     pub status_tagline: String,
+    pub secret_project: String,
 }
 
 #[derive(Deserialize)]
@@ -25,8 +27,10 @@ struct RichPresenceLocale {
     referral_button: Option<String>,
     referral_host: Option<String>,
     time_today_prefix: Option<String>,
+    time_rolling_prefix: Option<String>,
     time_logged_suffix: Option<String>,
     status_tagline: Option<String>,
+    secret_project: Option<String>,
 }
 
 static RICH_PRESENCE_TEXT: OnceLock<RichPresenceText> = OnceLock::new();
@@ -52,11 +56,13 @@ fn load_rich_presence_text() -> RichPresenceText {
         referral_button: text_or_default(rich_presence.referral_button, "Sign up"),
         referral_host: text_or_default(rich_presence.referral_host, "flavortown.hackclub.com"),
         time_today_prefix: text_or_default(rich_presence.time_today_prefix, "Today: "),
+        time_rolling_prefix: text_or_default(rich_presence.time_rolling_prefix, "Last 24h: "),
         time_logged_suffix: text_or_default(rich_presence.time_logged_suffix, " logged"),
         status_tagline: text_or_default(
             rich_presence.status_tagline,
             "Work on your personal projects, get rewarded with prizes. For teens ages <19",
         ),
+        secret_project: text_or_default(rich_presence.secret_project, "a secret project"),
     }
 }
 
@@ -0,0 +1,48 @@
+use crate::error::FlavortimeError;
+use serde_json::{Map, Value};
+
+pub const CONFIG_SCHEMA_VERSION: u32 = 2;
+
+type Migration = fn(&mut Map<String, Value>);
+
+const MIGRATIONS: &[(u32, Migration)] = &[(1, migrate_v1_to_v2)];
+
+pub fn schema_version(value: &Value) -> u32 {
+    value
+        .get("schema_version")
+        .and_then(Value::as_u64)
+        .and_then(|version| u32::try_from(version).ok())
+        .unwrap_or(1)
+}
+
+pub fn migrate_config(value: &mut Value) -> Result<u32, FlavortimeError> {
+    let original = schema_version(value);
+    let object = value
+        .as_object_mut()
+        .ok_or_else(|| FlavortimeError::Config("Config root is not an object".to_string()))?;
+
+    let mut version = original;
+    for (from, migration) in MIGRATIONS {
+        if version == *from {
+            migration(object);
+            version = from + 1;
+        }
+    }
+    object.insert("schema_version".to_string(), Value::from(version));
+    Ok(original)
+}
+
+fn migrate_v1_to_v2(config: &mut Map<String, Value>) {
+    config.retain(|_, value| !value.is_null());
+
+    if let Some(codes) = config.get_mut("available_referral_codes") {
+        if let Some(list) = codes.as_array_mut() {
+            list.retain(|code| {
+                code.get("code").is_some_and(Value::is_string)
+                    && code.get("code_type").is_some_and(Value::is_string)
+            });
+        } else {
+            *codes = Value::Array(Vec::new());
+        }
+    }
+}
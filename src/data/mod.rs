@@ -1,3 +1,11 @@
+pub mod cache;
 pub mod config;
+pub mod export;
+pub mod history;
 pub mod locale;
+pub mod migrations;
 pub mod runtime;
+pub mod secrets;
+pub mod sessions;
+pub mod stats;
+pub mod storage;
@@ -1,13 +1,36 @@
-use serde::Deserialize;
+use super::config::data_dir;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
 use std::sync::OnceLock;
 
-#[derive(Deserialize)]
+const ENV_PREFIX: &str = "FLAVORTIME_";
+
+#[derive(Default, Deserialize)]
+#[serde(deny_unknown_fields)]
 struct RuntimeToml {
     hackatime_base_url: Option<String>,
     pyramid_base_url: Option<String>,
     flavortown_base_url: Option<String>,
     flavortown_campaign_slug: Option<String>,
     discord_client_id: Option<u64>,
+    http_proxy: Option<String>,
+}
+
+#[derive(Clone, Copy, Serialize, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum RuntimeSource {
+    Baked,
+    File,
+    Env,
+}
+
+#[derive(Clone, Serialize)]
+pub struct RuntimeEntry {
+    pub name: &'static str,
+    pub value: Option<String>,
+    pub source: Option<RuntimeSource>,
 }
 
 pub struct Runtime {
@@ -16,6 +39,62 @@ pub struct Runtime {
     pub flavortown_base_url: String,
     pub flavortown_campaign_slug: String,
     pub discord_client_id: u64,
+    pub http_proxy: Option<String>,
+    pub entries: Vec<RuntimeEntry>,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct RuntimeReport {
+    pub entries: Vec<RuntimeEntry>,
+    pub warnings: Vec<String>,
+}
+
+struct Layers(Vec<(RuntimeSource, RuntimeToml)>);
+
+#[derive(Default)]
+struct Resolved {
+    entries: Vec<RuntimeEntry>,
+    warnings: Vec<String>,
+}
+
+impl Layers {
+    // Takes the highest layer whose value passes `check`. A bad user value
+    // falls through to the layer below with a warning; only a bad baked value
+    // is fatal.
+    fn pick<T>(
+        &self,
+        name: &'static str,
+        resolved: &mut Resolved,
+        field: impl Fn(&RuntimeToml) -> Option<T>,
+        check: impl Fn(T) -> Result<T, String>,
+        display: impl Fn(&T) -> String,
+    ) -> Result<Option<T>, String> {
+        let mut picked = None;
+        for (source, layer) in self.0.iter().rev() {
+            let Some(value) = field(layer) else {
+                continue;
+            };
+            match check(value) {
+                Ok(value) => {
+                    picked = Some((*source, value));
+                    break;
+                }
+                Err(err) if *source == RuntimeSource::Baked => return Err(err),
+                Err(err) => resolved.warnings.push(format!(
+                    "Ignoring {} from {}: {err}",
+                    name,
+                    origin(*source, name)
+                )),
+            }
+        }
+        resolved.entries.push(RuntimeEntry {
+            name,
+            value: picked.as_ref().map(|(_, value)| display(value)),
+            source: picked.as_ref().map(|(source, _)| *source),
+        });
+        Ok(picked.map(|(_, value)| value))
+    }
 }
 
 static RUNTIME: OnceLock<Runtime> = OnceLock::new();
@@ -24,44 +103,318 @@ pub fn runtime() -> &'static Runtime {
     RUNTIME.get().expect("runtime not initialized")
 }
 
+pub fn runtime_path() -> PathBuf {
+    data_dir().join("runtime.toml")
+}
+
 pub fn validate_startup_fields() -> Result<(), String> {
     if RUNTIME.get().is_some() {
         return Ok(());
     }
 
-    let parsed = toml::from_str::<RuntimeToml>(include_str!(concat!(
+    let baked = toml::from_str::<RuntimeToml>(include_str!(concat!(
         env!("CARGO_MANIFEST_DIR"),
         "/config.toml"
     )))
     .map_err(|err| format!("Failed to parse config.toml: {err}"))?;
-    let runtime = Runtime {
-        hackatime_base_url: required_text(parsed.hackatime_base_url, "hackatime_base_url")?,
-        pyramid_base_url: required_text(parsed.pyramid_base_url, "pyramid_base_url")?,
-        flavortown_base_url: required_text(parsed.flavortown_base_url, "flavortown_base_url")?,
-        flavortown_campaign_slug: required_text(
-            parsed.flavortown_campaign_slug,
-            "flavortown_campaign_slug",
-        )?,
-        discord_client_id: required(parsed.discord_client_id, "discord_client_id")?,
-    };
+    let (file, file_warning) = user_layer();
+    let (env, env_warning) = env_layer();
+    let layers = Layers(vec![
+        (RuntimeSource::Baked, baked),
+        (RuntimeSource::File, file),
+        (RuntimeSource::Env, env),
+    ]);
 
+    let mut runtime = resolve(&layers)?;
+    let mut warnings: Vec<String> = file_warning.into_iter().chain(env_warning).collect();
+    warnings.append(&mut runtime.warnings);
+    runtime.warnings = warnings;
     let _ = RUNTIME.set(runtime);
     Ok(())
 }
 
+impl Runtime {
+    pub fn report(&self) -> RuntimeReport {
+        RuntimeReport {
+            entries: self.entries.clone(),
+            warnings: self.warnings.clone(),
+        }
+    }
+}
+
+fn resolve(layers: &Layers) -> Result<Runtime, String> {
+    let mut resolved = Resolved::default();
+    let text = |value: &String| value.clone();
+    let url = |name| move |value: String| base_url(value, name);
+    let hackatime_base_url = layers.pick(
+        "hackatime_base_url",
+        &mut resolved,
+        |layer| optional_text(layer.hackatime_base_url.clone()),
+        url("hackatime_base_url"),
+        text,
+    )?;
+    let pyramid_base_url = layers.pick(
+        "pyramid_base_url",
+        &mut resolved,
+        |layer| optional_text(layer.pyramid_base_url.clone()),
+        url("pyramid_base_url"),
+        text,
+    )?;
+    let flavortown_base_url = layers.pick(
+        "flavortown_base_url",
+        &mut resolved,
+        |layer| optional_text(layer.flavortown_base_url.clone()),
+        url("flavortown_base_url"),
+        text,
+    )?;
+    let flavortown_campaign_slug = layers.pick(
+        "flavortown_campaign_slug",
+        &mut resolved,
+        |layer| optional_text(layer.flavortown_campaign_slug.clone()),
+        Ok,
+        text,
+    )?;
+    let discord_client_id = layers.pick(
+        "discord_client_id",
+        &mut resolved,
+        |layer| layer.discord_client_id,
+        Ok,
+        u64::to_string,
+    )?;
+    let http_proxy = layers.pick(
+        "http_proxy",
+        &mut resolved,
+        |layer| optional_text(layer.http_proxy.clone()),
+        |proxy| validate_url(&proxy, "http_proxy", &["http", "https", "socks5"]).map(|()| proxy),
+        text,
+    )?;
+
+    Ok(Runtime {
+        hackatime_base_url: required(hackatime_base_url, "hackatime_base_url")?,
+        pyramid_base_url: required(pyramid_base_url, "pyramid_base_url")?,
+        flavortown_base_url: required(flavortown_base_url, "flavortown_base_url")?,
+        flavortown_campaign_slug: required(flavortown_campaign_slug, "flavortown_campaign_slug")?,
+        discord_client_id: required(discord_client_id, "discord_client_id")?,
+        http_proxy,
+        entries: resolved.entries,
+        warnings: resolved.warnings,
+    })
+}
+
+fn user_layer() -> (RuntimeToml, Option<String>) {
+    let path = runtime_path();
+    let error = match fs::read_to_string(&path) {
+        Ok(raw) => match toml::from_str(&raw) {
+            Ok(layer) => return (layer, None),
+            Err(err) => format!("Ignoring {}: {err}", path.display()),
+        },
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            return (RuntimeToml::default(), None)
+        }
+        Err(err) => format!("Ignoring unreadable {}: {err}", path.display()),
+    };
+    (RuntimeToml::default(), Some(error))
+}
+
+fn env_layer() -> (RuntimeToml, Option<String>) {
+    let mut warning = None;
+    let discord_client_id = env_value("discord_client_id").and_then(|value| {
+        value.parse::<u64>().ok().or_else(|| {
+            warning = Some(format!(
+                "Ignoring {}: {value} is not a number",
+                origin(RuntimeSource::Env, "discord_client_id")
+            ));
+            None
+        })
+    });
+
+    let layer = RuntimeToml {
+        hackatime_base_url: env_value("hackatime_base_url"),
+        pyramid_base_url: env_value("pyramid_base_url"),
+        flavortown_base_url: env_value("flavortown_base_url"),
+        flavortown_campaign_slug: env_value("flavortown_campaign_slug"),
+        discord_client_id,
+        http_proxy: env_value("http_proxy"),
+    };
+    (layer, warning)
+}
+
+fn origin(source: RuntimeSource, name: &str) -> String {
+    match source {
+        RuntimeSource::Baked => "config.toml".to_string(),
+        RuntimeSource::File => "runtime.toml".to_string(),
+        RuntimeSource::Env => format!("{ENV_PREFIX}{}", name.to_ascii_uppercase()),
+    }
+}
+
+fn env_value(name: &str) -> Option<String> {
+    let key = format!("{ENV_PREFIX}{}", name.to_ascii_uppercase());
+    optional_text(std::env::var(key).ok())
+}
+
+fn base_url(value: String, name: &str) -> Result<String, String> {
+    validate_url(&value, name, &["http", "https"])?;
+    Ok(value.trim_end_matches('/').to_string())
+}
+
+fn validate_url(value: &str, name: &str, schemes: &[&str]) -> Result<(), String> {
+    let url = Url::parse(value).map_err(|err| format!("Invalid URL for {name}: {err}"))?;
+    if !schemes.contains(&url.scheme()) || url.host_str().is_none() {
+        return Err(format!("Invalid URL for {name}: {value}"));
+    }
+    Ok(())
+}
+
 fn required<T>(value: Option<T>, name: &str) -> Result<T, String> {
     value.ok_or_else(|| missing_field(name))
 }
 
-fn required_text(value: Option<String>, name: &str) -> Result<String, String> {
-    let value = required(value, name)?;
-    let trimmed = value.trim();
-    if trimmed.is_empty() {
-        return Err(missing_field(name));
-    }
-    Ok(trimmed.to_string())
+fn optional_text(value: Option<String>) -> Option<String> {
+    value
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
 }
 
 fn missing_field(name: &str) -> String {
     format!("Missing required field: {name}")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layer(hackatime_base_url: Option<&str>, discord_client_id: Option<u64>) -> RuntimeToml {
+        RuntimeToml {
+            hackatime_base_url: hackatime_base_url.map(str::to_string),
+            discord_client_id,
+            ..RuntimeToml::default()
+        }
+    }
+
+    fn baked() -> RuntimeToml {
+        RuntimeToml {
+            hackatime_base_url: Some("https://baked.example/".to_string()),
+            pyramid_base_url: Some("https://pyramid.example".to_string()),
+            flavortown_base_url: Some("https://flavortown.example".to_string()),
+            flavortown_campaign_slug: Some("flavortown".to_string()),
+            discord_client_id: Some(1),
+            http_proxy: None,
+        }
+    }
+
+    fn source(runtime: &Runtime, name: &str) -> Option<RuntimeSource> {
+        runtime
+            .entries
+            .iter()
+            .find(|entry| entry.name == name)
+            .and_then(|entry| entry.source)
+    }
+
+    #[test]
+    fn later_layers_override_earlier_ones() {
+        let runtime = resolve(&Layers(vec![
+            (RuntimeSource::Baked, baked()),
+            (
+                RuntimeSource::File,
+                layer(Some("https://file.example"), Some(2)),
+            ),
+            (RuntimeSource::Env, layer(Some("  "), Some(3))),
+        ]))
+        .unwrap();
+
+        assert_eq!(runtime.hackatime_base_url, "https://file.example");
+        assert_eq!(
+            source(&runtime, "hackatime_base_url"),
+            Some(RuntimeSource::File)
+        );
+        assert_eq!(runtime.discord_client_id, 3);
+        assert_eq!(
+            source(&runtime, "discord_client_id"),
+            Some(RuntimeSource::Env)
+        );
+        assert_eq!(runtime.pyramid_base_url, "https://pyramid.example");
+        assert_eq!(
+            source(&runtime, "pyramid_base_url"),
+            Some(RuntimeSource::Baked)
+        );
+        assert_eq!(source(&runtime, "http_proxy"), None);
+    }
+
+    #[test]
+    fn invalid_user_values_fall_back_with_a_warning() {
+        let runtime = resolve(&Layers(vec![
+            (RuntimeSource::Baked, baked()),
+            (RuntimeSource::File, layer(Some("not a url"), None)),
+            (RuntimeSource::Env, layer(None, None)),
+        ]))
+        .unwrap();
+        assert_eq!(runtime.hackatime_base_url, "https://baked.example");
+        assert_eq!(
+            source(&runtime, "hackatime_base_url"),
+            Some(RuntimeSource::Baked)
+        );
+        assert_eq!(runtime.warnings.len(), 1);
+        assert!(runtime.warnings[0].contains("hackatime_base_url from runtime.toml"));
+
+        let runtime = resolve(&Layers(vec![
+            (RuntimeSource::Baked, baked()),
+            (
+                RuntimeSource::File,
+                layer(Some("https://file.example"), None),
+            ),
+            (RuntimeSource::Env, layer(Some("ftp://files.example"), None)),
+        ]))
+        .unwrap();
+        assert_eq!(runtime.hackatime_base_url, "https://file.example");
+        assert_eq!(
+            source(&runtime, "hackatime_base_url"),
+            Some(RuntimeSource::File)
+        );
+        assert!(runtime.warnings[0].contains("FLAVORTIME_HACKATIME_BASE_URL"));
+        assert_eq!(runtime.report().warnings, runtime.warnings);
+    }
+
+    #[test]
+    fn invalid_baked_values_are_fatal() {
+        let mut broken = baked();
+        broken.pyramid_base_url = Some("ftp://files.example".to_string());
+        let err = resolve(&Layers(vec![(RuntimeSource::Baked, broken)]))
+            .err()
+            .unwrap();
+        assert!(err.contains("pyramid_base_url"));
+
+        let mut missing = baked();
+        missing.flavortown_campaign_slug = None;
+        let err = resolve(&Layers(vec![(RuntimeSource::Baked, missing)]))
+            .err()
+            .unwrap();
+        assert_eq!(err, missing_field("flavortown_campaign_slug"));
+    }
+
+    #[test]
+    fn malformed_runtime_file_is_ignored_with_a_warning() {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        super::super::config::set_test_data_dir(dir.path());
+
+        let (layer, warning) = user_layer();
+        assert!(layer.hackatime_base_url.is_none());
+        assert!(warning.is_none());
+
+        fs::write(
+            runtime_path(),
+            "hackatime_base_url = \"https://file.example\"",
+        )
+        .unwrap();
+        let (layer, warning) = user_layer();
+        assert_eq!(
+            layer.hackatime_base_url.as_deref(),
+            Some("https://file.example")
+        );
+        assert!(warning.is_none());
+
+        fs::write(runtime_path(), "hackatime_base_url = [").unwrap();
+        let (layer, warning) = user_layer();
+        assert!(layer.hackatime_base_url.is_none());
+        assert!(warning.is_some_and(|warning| warning.contains("runtime.toml")));
+    }
+}
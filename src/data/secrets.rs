@@ -0,0 +1,412 @@
+use super::storage::write_atomic_private;
+use crate::error::FlavortimeError;
+use chacha20poly1305::{
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+    ChaCha20Poly1305, Key, Nonce,
+};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+#[cfg(not(test))]
+use std::sync::OnceLock;
+
+pub const FLAVORTOWN_API_KEY: &str = "flavortown_api_key";
+pub const WAKATIME_API_KEY: &str = "wakatime_api_key";
+
+const NONCE_LEN: usize = 12;
+const KEY_CONTEXT: &[u8] = b"flavortime-secret-store-v1";
+
+pub trait SecretStore: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn get(&self, key: &str) -> Result<Option<String>, FlavortimeError>;
+    fn set(&self, key: &str, value: &str) -> Result<(), FlavortimeError>;
+    fn delete(&self, key: &str) -> Result<(), FlavortimeError>;
+}
+
+pub struct FileSecretStore {
+    dir: PathBuf,
+    io: Mutex<()>,
+}
+
+impl FileSecretStore {
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            io: Mutex::new(()),
+        }
+    }
+
+    fn secrets_path(&self) -> PathBuf {
+        self.dir.join("secrets.json")
+    }
+
+    fn machine_secret_path(&self) -> PathBuf {
+        self.dir.join("secret.key")
+    }
+
+    fn cipher(&self) -> Result<ChaCha20Poly1305, FlavortimeError> {
+        let path = self.machine_secret_path();
+        let secret = match fs::read(&path) {
+            Ok(secret) if !secret.is_empty() => secret,
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => {
+                return Err(secret_error(err))
+            }
+            _ => {
+                let secret = ChaCha20Poly1305::generate_key(&mut OsRng).to_vec();
+                write_private(&path, &secret)?;
+                secret
+            }
+        };
+
+        let mut hasher = Sha256::new();
+        hasher.update(KEY_CONTEXT);
+        hasher.update(&secret);
+        let key = hasher.finalize();
+        Ok(ChaCha20Poly1305::new(Key::from_slice(&key)))
+    }
+
+    fn read_entries(&self) -> Result<BTreeMap<String, String>, FlavortimeError> {
+        match fs::read_to_string(self.secrets_path()) {
+            Ok(raw) => serde_json::from_str(&raw).map_err(secret_error),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+            Err(err) => Err(secret_error(err)),
+        }
+    }
+
+    fn write_entries(&self, entries: &BTreeMap<String, String>) -> Result<(), FlavortimeError> {
+        let raw = serde_json::to_string_pretty(entries).map_err(secret_error)?;
+        write_private(&self.secrets_path(), raw.as_bytes())
+    }
+}
+
+impl SecretStore for FileSecretStore {
+    fn name(&self) -> &'static str {
+        "file"
+    }
+
+    fn get(&self, key: &str) -> Result<Option<String>, FlavortimeError> {
+        let _guard = self.io.lock().map_err(|_| FlavortimeError::Lock)?;
+        let entries = self.read_entries()?;
+        let Some(encoded) = entries.get(key) else {
+            return Ok(None);
+        };
+
+        let sealed = decode_hex(encoded)
+            .filter(|sealed| sealed.len() > NONCE_LEN)
+            .ok_or_else(|| secret_error("Stored secret is malformed"))?;
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        let plaintext = self
+            .cipher()?
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| secret_error("Stored secret could not be decrypted"))?;
+        String::from_utf8(plaintext).map(Some).map_err(secret_error)
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<(), FlavortimeError> {
+        let _guard = self.io.lock().map_err(|_| FlavortimeError::Lock)?;
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher()?
+            .encrypt(&nonce, value.as_bytes())
+            .map_err(|_| secret_error("Secret could not be encrypted"))?;
+
+        let mut sealed = nonce.to_vec();
+        sealed.extend_from_slice(&ciphertext);
+        let mut entries = self.read_entries()?;
+        entries.insert(key.to_string(), encode_hex(&sealed));
+        self.write_entries(&entries)
+    }
+
+    fn delete(&self, key: &str) -> Result<(), FlavortimeError> {
+        let _guard = self.io.lock().map_err(|_| FlavortimeError::Lock)?;
+        let mut entries = self.read_entries()?;
+        if entries.remove(key).is_some() {
+            self.write_entries(&entries)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+pub struct KeyringSecretStore {
+    service: &'static str,
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+impl KeyringSecretStore {
+    pub fn new(service: &'static str) -> Self {
+        Self { service }
+    }
+
+    fn entry(&self, key: &str) -> Result<keyring::Entry, FlavortimeError> {
+        keyring::Entry::new(self.service, key).map_err(secret_error)
+    }
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+impl SecretStore for KeyringSecretStore {
+    fn name(&self) -> &'static str {
+        "keyring"
+    }
+
+    fn get(&self, key: &str) -> Result<Option<String>, FlavortimeError> {
+        match self.entry(key)?.get_password() {
+            Ok(value) => Ok(Some(value)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(err) => Err(secret_error(err)),
+        }
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<(), FlavortimeError> {
+        self.entry(key)?.set_password(value).map_err(secret_error)
+    }
+
+    fn delete(&self, key: &str) -> Result<(), FlavortimeError> {
+        match self.entry(key)?.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(err) => Err(secret_error(err)),
+        }
+    }
+}
+
+pub struct ChainedSecretStore {
+    stores: Vec<Box<dyn SecretStore>>,
+}
+
+impl ChainedSecretStore {
+    pub fn new(stores: Vec<Box<dyn SecretStore>>) -> Self {
+        Self { stores }
+    }
+}
+
+impl SecretStore for ChainedSecretStore {
+    fn name(&self) -> &'static str {
+        "chained"
+    }
+
+    fn get(&self, key: &str) -> Result<Option<String>, FlavortimeError> {
+        let mut last_error = None;
+        for store in &self.stores {
+            match store.get(key) {
+                Ok(Some(value)) => return Ok(Some(value)),
+                Ok(None) => {}
+                Err(err) => {
+                    log::warn!("Secret store `{}` read failed: {err}", store.name());
+                    last_error = Some(err);
+                }
+            }
+        }
+        last_error.map_or(Ok(None), Err)
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<(), FlavortimeError> {
+        let mut last_error = None;
+        for (index, store) in self.stores.iter().enumerate() {
+            match store.set(key, value) {
+                Ok(()) => {
+                    for other in self.stores.iter().skip(index + 1) {
+                        let _ = other.delete(key);
+                    }
+                    return Ok(());
+                }
+                Err(err) => {
+                    log::warn!("Secret store `{}` write failed: {err}", store.name());
+                    last_error = Some(err);
+                }
+            }
+        }
+        Err(last_error.unwrap_or_else(|| secret_error("No secret store available")))
+    }
+
+    fn delete(&self, key: &str) -> Result<(), FlavortimeError> {
+        let mut result = Ok(());
+        for store in &self.stores {
+            if let Err(err) = store.delete(key) {
+                log::warn!("Secret store `{}` delete failed: {err}", store.name());
+                result = Err(err);
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+#[derive(Default)]
+pub struct MemorySecretStore {
+    entries: Mutex<BTreeMap<String, String>>,
+}
+
+#[cfg(test)]
+impl SecretStore for MemorySecretStore {
+    fn name(&self) -> &'static str {
+        "memory"
+    }
+
+    fn get(&self, key: &str) -> Result<Option<String>, FlavortimeError> {
+        let entries = self.entries.lock().map_err(|_| FlavortimeError::Lock)?;
+        Ok(entries.get(key).cloned())
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<(), FlavortimeError> {
+        let mut entries = self.entries.lock().map_err(|_| FlavortimeError::Lock)?;
+        entries.insert(key.to_string(), value.to_string());
+        Ok(())
+    }
+
+    fn delete(&self, key: &str) -> Result<(), FlavortimeError> {
+        let mut entries = self.entries.lock().map_err(|_| FlavortimeError::Lock)?;
+        entries.remove(key);
+        Ok(())
+    }
+}
+
+#[cfg(not(test))]
+pub fn secret_store() -> &'static dyn SecretStore {
+    static SECRET_STORE: OnceLock<Box<dyn SecretStore>> = OnceLock::new();
+    SECRET_STORE.get_or_init(default_store).as_ref()
+}
+
+#[cfg(test)]
+pub fn secret_store() -> &'static dyn SecretStore {
+    thread_local! {
+        static TEST_STORE: &'static MemorySecretStore = Box::leak(Box::default());
+    }
+    TEST_STORE.with(|store| *store)
+}
+
+#[cfg(not(test))]
+fn default_store() -> Box<dyn SecretStore> {
+    let file_store: Box<dyn SecretStore> =
+        Box::new(FileSecretStore::new(super::config::data_dir()));
+
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    {
+        Box::new(ChainedSecretStore::new(vec![
+            Box::new(KeyringSecretStore::new("com.hackclub.flavortime")),
+            file_store,
+        ]))
+    }
+
+    // Linux has no keyring backend: Secret Service needs a D-Bus session and
+    // libdbus at runtime, which tray-only and headless desktops often lack.
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    {
+        Box::new(ChainedSecretStore::new(vec![file_store]))
+    }
+}
+
+fn write_private(path: &Path, contents: &[u8]) -> Result<(), FlavortimeError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(secret_error)?;
+    }
+    write_atomic_private(path, contents).map_err(secret_error)
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn decode_hex(value: &str) -> Option<Vec<u8>> {
+    if !value.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..value.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(value.get(index..index + 2)?, 16).ok())
+        .collect()
+}
+
+fn secret_error(err: impl ToString) -> FlavortimeError {
+    FlavortimeError::SecretStore(err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_store_round_trips_encrypted_secret() {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let store = FileSecretStore::new(dir.path().to_path_buf());
+
+        assert_eq!(store.get(FLAVORTOWN_API_KEY).unwrap(), None);
+        store.set(FLAVORTOWN_API_KEY, "hunter2").unwrap();
+        assert_eq!(
+            store.get(FLAVORTOWN_API_KEY).unwrap().as_deref(),
+            Some("hunter2")
+        );
+
+        let raw = fs::read_to_string(store.secrets_path()).unwrap();
+        assert!(!raw.contains("hunter2"));
+
+        store.delete(FLAVORTOWN_API_KEY).unwrap();
+        assert_eq!(store.get(FLAVORTOWN_API_KEY).unwrap(), None);
+    }
+
+    #[test]
+    fn file_store_rejects_tampered_secret() {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let store = FileSecretStore::new(dir.path().to_path_buf());
+        store.set(FLAVORTOWN_API_KEY, "hunter2").unwrap();
+        fs::write(store.machine_secret_path(), b"another machine").unwrap();
+
+        assert!(matches!(
+            store.get(FLAVORTOWN_API_KEY),
+            Err(FlavortimeError::SecretStore(_))
+        ));
+    }
+
+    #[test]
+    fn chained_store_falls_through_and_cleans_up() {
+        let primary = MemorySecretStore::default();
+        primary.set("other", "value").unwrap();
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let fallback = FileSecretStore::new(dir.path().to_path_buf());
+        fallback.set(FLAVORTOWN_API_KEY, "legacy").unwrap();
+
+        let chained = ChainedSecretStore::new(vec![Box::new(primary), Box::new(fallback)]);
+        assert_eq!(
+            chained.get(FLAVORTOWN_API_KEY).unwrap().as_deref(),
+            Some("legacy")
+        );
+
+        chained.set(FLAVORTOWN_API_KEY, "fresh").unwrap();
+        assert_eq!(
+            chained.get(FLAVORTOWN_API_KEY).unwrap().as_deref(),
+            Some("fresh")
+        );
+        assert_eq!(chained.stores[1].get(FLAVORTOWN_API_KEY).unwrap(), None);
+    }
+
+    #[test]
+    fn file_store_keeps_unreadable_machine_key() {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let store = FileSecretStore::new(dir.path().to_path_buf());
+        fs::create_dir(store.machine_secret_path()).unwrap();
+
+        assert!(matches!(
+            store.set(FLAVORTOWN_API_KEY, "hunter2"),
+            Err(FlavortimeError::SecretStore(_))
+        ));
+        assert!(store.machine_secret_path().is_dir());
+        assert!(!store.secrets_path().exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn file_store_writes_owner_only_files() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let store = FileSecretStore::new(dir.path().to_path_buf());
+        store.set(FLAVORTOWN_API_KEY, "hunter2").unwrap();
+
+        for path in [store.secrets_path(), store.machine_secret_path()] {
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600, "{}", path.display());
+        }
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 2);
+    }
+}
@@ -0,0 +1,384 @@
+use super::config::data_dir;
+use super::history::parse_date;
+use super::storage::{write_atomic, DirLock};
+use crate::error::FlavortimeError;
+use chrono::{Local, NaiveDate, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+const PROGRESS_INTERVAL_SECS: u64 = 300;
+const DISCORD_TICK_CAP_SECS: u64 = 120;
+const RETENTION_DAYS: u64 = 365;
+
+static SEQUENCE: AtomicU32 = AtomicU32::new(0);
+
+#[derive(Clone, Copy, Default, PartialEq, Debug, Serialize, Deserialize)]
+pub struct SessionCounters {
+    pub sharing_seconds: u64,
+    pub discord_seconds: u64,
+    pub heartbeats_ok: u32,
+    pub heartbeats_failed: u32,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum SessionEvent {
+    Started {
+        session: String,
+        at: u64,
+    },
+    Project {
+        session: String,
+        at: u64,
+        project: Option<String>,
+        total_hours: f64,
+    },
+    HeartbeatFailed {
+        session: String,
+        at: u64,
+        error: String,
+    },
+    Progress {
+        session: String,
+        at: u64,
+        counters: SessionCounters,
+    },
+    Ended {
+        session: String,
+        at: u64,
+    },
+}
+
+impl SessionEvent {
+    fn session(&self) -> &str {
+        match self {
+            Self::Started { session, .. }
+            | Self::Project { session, .. }
+            | Self::HeartbeatFailed { session, .. }
+            | Self::Progress { session, .. }
+            | Self::Ended { session, .. } => session,
+        }
+    }
+
+    fn at(&self) -> u64 {
+        match self {
+            Self::Started { at, .. }
+            | Self::Project { at, .. }
+            | Self::HeartbeatFailed { at, .. }
+            | Self::Progress { at, .. }
+            | Self::Ended { at, .. } => *at,
+        }
+    }
+}
+
+#[derive(Clone, Serialize)]
+pub struct ProjectSnapshot {
+    pub at: u64,
+    pub project: Option<String>,
+    pub total_hours: f64,
+}
+
+#[derive(Clone, Serialize)]
+pub struct SessionSummary {
+    pub id: String,
+    pub started_at: u64,
+    pub ended_at: Option<u64>,
+    pub last_seen_at: u64,
+    #[serde(flatten)]
+    pub counters: SessionCounters,
+    pub last_error: Option<String>,
+    pub projects: Vec<ProjectSnapshot>,
+}
+
+pub struct ActiveSession {
+    id: String,
+    counters: SessionCounters,
+    project: Option<Option<String>>,
+    discord_tick: Option<u64>,
+    flushed_at: u64,
+    dirty: bool,
+}
+
+impl ActiveSession {
+    pub fn start(at: u64) -> Self {
+        let sequence = SEQUENCE.fetch_add(1, Ordering::Relaxed);
+        let id = format!("{at:x}-{:x}-{sequence}", std::process::id());
+        log_append(&SessionEvent::Started {
+            session: id.clone(),
+            at,
+        });
+        Self {
+            id,
+            counters: SessionCounters::default(),
+            project: None,
+            discord_tick: None,
+            flushed_at: at,
+            dirty: false,
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn counters(&self) -> SessionCounters {
+        self.counters
+    }
+
+    pub fn add_sharing(&mut self, seconds: u64) {
+        if seconds > 0 {
+            self.counters.sharing_seconds = self.counters.sharing_seconds.saturating_add(seconds);
+            self.dirty = true;
+        }
+    }
+
+    pub fn observe_discord(&mut self, connected: bool, at: u64) {
+        if let (true, Some(previous)) = (connected, self.discord_tick) {
+            let elapsed = at.saturating_sub(previous).min(DISCORD_TICK_CAP_SECS);
+            self.counters.discord_seconds = self.counters.discord_seconds.saturating_add(elapsed);
+            self.dirty |= elapsed > 0;
+        }
+        self.discord_tick = connected.then_some(at);
+    }
+
+    pub fn observe_project(&mut self, project: Option<&str>, total_hours: f64, at: u64) {
+        let project = project.map(str::to_string);
+        if self.project.as_ref() == Some(&project) {
+            return;
+        }
+        log_append(&SessionEvent::Project {
+            session: self.id.clone(),
+            at,
+            project: project.clone(),
+            total_hours,
+        });
+        self.project = Some(project);
+    }
+
+    pub fn record_heartbeat(&mut self, result: Result<(), String>, at: u64) {
+        self.dirty = true;
+        match result {
+            Ok(()) => self.counters.heartbeats_ok = self.counters.heartbeats_ok.saturating_add(1),
+            Err(error) => {
+                self.counters.heartbeats_failed = self.counters.heartbeats_failed.saturating_add(1);
+                log_append(&SessionEvent::HeartbeatFailed {
+                    session: self.id.clone(),
+                    at,
+                    error,
+                });
+            }
+        }
+    }
+
+    pub fn flush_if_due(&mut self, at: u64) {
+        if self.dirty && at.saturating_sub(self.flushed_at) >= PROGRESS_INTERVAL_SECS {
+            self.flush(at);
+        }
+    }
+
+    pub fn end(mut self, at: u64) {
+        if self.dirty {
+            self.flush(at);
+        }
+        log_append(&SessionEvent::Ended {
+            session: self.id,
+            at,
+        });
+    }
+
+    fn flush(&mut self, at: u64) {
+        log_append(&SessionEvent::Progress {
+            session: self.id.clone(),
+            at,
+            counters: self.counters,
+        });
+        self.flushed_at = at;
+        self.dirty = false;
+    }
+}
+
+pub fn append(event: &SessionEvent) -> Result<(), FlavortimeError> {
+    let mut line = serde_json::to_string(event)?;
+    line.push('\n');
+    let _lock = DirLock::acquire(&data_dir())?;
+    let mut file = OpenOptions::new().create(true).append(true).open(path())?;
+    file.write_all(line.as_bytes())?;
+    Ok(())
+}
+
+fn log_append(event: &SessionEvent) {
+    if let Err(err) = append(event) {
+        log::warn!("Failed to record session event: {err}");
+    }
+}
+
+pub fn load_events() -> Vec<SessionEvent> {
+    let Ok(raw) = fs::read_to_string(path()) else {
+        return Vec::new();
+    };
+    raw.lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+pub fn summarize(events: impl IntoIterator<Item = SessionEvent>) -> Vec<SessionSummary> {
+    let mut sessions: Vec<SessionSummary> = Vec::new();
+    let mut index = HashMap::<String, usize>::new();
+
+    for event in events {
+        let at = event.at();
+        let position = *index.entry(event.session().to_string()).or_insert_with(|| {
+            sessions.push(SessionSummary {
+                id: event.session().to_string(),
+                started_at: at,
+                ended_at: None,
+                last_seen_at: at,
+                counters: SessionCounters::default(),
+                last_error: None,
+                projects: Vec::new(),
+            });
+            sessions.len() - 1
+        });
+        let summary = &mut sessions[position];
+        summary.last_seen_at = summary.last_seen_at.max(at);
+
+        match event {
+            SessionEvent::Started { .. } => summary.started_at = summary.started_at.min(at),
+            SessionEvent::Project {
+                project,
+                total_hours,
+                ..
+            } => summary.projects.push(ProjectSnapshot {
+                at,
+                project,
+                total_hours,
+            }),
+            SessionEvent::HeartbeatFailed { error, .. } => summary.last_error = Some(error),
+            SessionEvent::Progress { counters, .. } => summary.counters = counters,
+            SessionEvent::Ended { .. } => summary.ended_at = Some(at),
+        }
+    }
+
+    sessions
+}
+
+pub fn query(from: u64, until: u64) -> Vec<SessionSummary> {
+    summarize(load_events())
+        .into_iter()
+        .filter(|session| session.started_at < until && session.last_seen_at >= from)
+        .collect()
+}
+
+pub fn date_range(start_date: &str, end_date: &str) -> Result<(u64, u64), FlavortimeError> {
+    let (start, end) = (parse_date(start_date)?, parse_date(end_date)?);
+    if end < start {
+        return Err(FlavortimeError::InvalidInput(
+            "End date must not be before start date".to_string(),
+        ));
+    }
+
+    let midnight = |date: NaiveDate| {
+        date.and_hms_opt(0, 0, 0)
+            .and_then(|time| Local.from_local_datetime(&time).earliest())
+            .map_or(0, |time| time.timestamp().max(0) as u64)
+    };
+    let after_end = end.succ_opt().unwrap_or(end);
+    Ok((midnight(start), midnight(after_end)))
+}
+
+pub fn prune() -> Result<(), FlavortimeError> {
+    let now = Utc::now().timestamp().max(0) as u64;
+    let cutoff = now.saturating_sub(RETENTION_DAYS * 86_400);
+    let _lock = DirLock::acquire(&data_dir())?;
+    let events = load_events();
+    let expired: Vec<String> = summarize(events.iter().cloned())
+        .into_iter()
+        .filter(|session| session.last_seen_at < cutoff)
+        .map(|session| session.id)
+        .collect();
+    if expired.is_empty() {
+        return Ok(());
+    }
+
+    let mut raw = String::new();
+    for event in events
+        .iter()
+        .filter(|event| !expired.iter().any(|id| id == event.session()))
+    {
+        raw.push_str(&serde_json::to_string(event)?);
+        raw.push('\n');
+    }
+    write_atomic(&path(), raw.as_bytes())
+}
+
+fn path() -> PathBuf {
+    data_dir().join("sessions.jsonl")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn progress(session: &str, at: u64, sharing_seconds: u64) -> SessionEvent {
+        SessionEvent::Progress {
+            session: session.to_string(),
+            at,
+            counters: SessionCounters {
+                sharing_seconds,
+                ..SessionCounters::default()
+            },
+        }
+    }
+
+    #[test]
+    fn summaries_keep_latest_progress_per_session() {
+        let events = vec![
+            SessionEvent::Started {
+                session: "a".to_string(),
+                at: 100,
+            },
+            progress("a", 400, 120),
+            SessionEvent::Started {
+                session: "b".to_string(),
+                at: 500,
+            },
+            progress("a", 700, 300),
+            SessionEvent::Ended {
+                session: "a".to_string(),
+                at: 800,
+            },
+            SessionEvent::HeartbeatFailed {
+                session: "b".to_string(),
+                at: 900,
+                error: "offline".to_string(),
+            },
+        ];
+
+        let sessions = summarize(events);
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].counters.sharing_seconds, 300);
+        assert_eq!(sessions[0].ended_at, Some(800));
+        assert_eq!(sessions[1].ended_at, None);
+        assert_eq!(sessions[1].last_seen_at, 900);
+        assert_eq!(sessions[1].last_error.as_deref(), Some("offline"));
+    }
+
+    #[test]
+    fn discord_time_only_counts_connected_intervals() {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        super::super::config::set_test_data_dir(dir.path());
+
+        let mut session = ActiveSession::start(1_000);
+        session.observe_discord(true, 1_000);
+        session.observe_discord(true, 1_060);
+        session.observe_discord(false, 1_120);
+        session.observe_discord(true, 1_180);
+        session.observe_discord(true, 1_600);
+        assert_eq!(session.counters().discord_seconds, 60 + 120);
+    }
+}
@@ -0,0 +1,227 @@
+use super::config::data_dir;
+use super::storage::{write_atomic, DirLock};
+use crate::error::FlavortimeError;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+pub const TOP_PROJECTS: usize = 5;
+pub const STATS_CACHE_DAYS: usize = 366;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StatsRange {
+    Week,
+    Month,
+}
+
+impl StatsRange {
+    pub fn days(self) -> u32 {
+        match self {
+            Self::Week => 7,
+            Self::Month => 30,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ProjectTotal {
+    pub name: String,
+    pub seconds: f64,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DayProjects {
+    pub date: String,
+    pub projects: Vec<ProjectTotal>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct DayBreakdown {
+    pub date: String,
+    pub seconds: f64,
+    pub top_project: Option<String>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct StatsReport {
+    pub range: StatsRange,
+    pub start_date: String,
+    pub end_date: String,
+    pub total_seconds: f64,
+    pub daily_average_seconds: f64,
+    pub projects: Vec<ProjectTotal>,
+    pub top_projects: Vec<ProjectTotal>,
+    pub days: Vec<DayBreakdown>,
+}
+
+impl StatsReport {
+    pub fn aggregate(range: StatsRange, days: &[DayProjects]) -> Self {
+        let breakdown: Vec<DayBreakdown> = days
+            .iter()
+            .map(|day| DayBreakdown {
+                date: day.date.clone(),
+                seconds: day.projects.iter().map(|project| project.seconds).sum(),
+                top_project: day.projects.first().map(|project| project.name.clone()),
+            })
+            .collect();
+
+        let projects = project_totals(days);
+        let total_seconds: f64 = projects.iter().map(|project| project.seconds).sum();
+
+        Self {
+            range,
+            start_date: days.first().map(|day| day.date.clone()).unwrap_or_default(),
+            end_date: days.last().map(|day| day.date.clone()).unwrap_or_default(),
+            total_seconds,
+            daily_average_seconds: total_seconds / days.len().max(1) as f64,
+            top_projects: projects.iter().take(TOP_PROJECTS).cloned().collect(),
+            projects,
+            days: breakdown,
+        }
+    }
+}
+
+pub fn project_totals(days: &[DayProjects]) -> Vec<ProjectTotal> {
+    let mut totals = HashMap::<&str, f64>::new();
+    for project in days.iter().flat_map(|day| &day.projects) {
+        *totals.entry(project.name.as_str()).or_default() += project.seconds;
+    }
+    sorted(
+        totals
+            .into_iter()
+            .map(|(name, seconds)| ProjectTotal {
+                name: name.to_string(),
+                seconds,
+            })
+            .collect(),
+    )
+}
+
+pub fn day_projects(date: &str, projects: impl IntoIterator<Item = (String, f64)>) -> DayProjects {
+    let mut merged = HashMap::<String, f64>::new();
+    for (name, seconds) in projects {
+        *merged.entry(name).or_default() += seconds.max(0.0);
+    }
+    DayProjects {
+        date: date.to_string(),
+        projects: sorted(
+            merged
+                .into_iter()
+                .filter(|(_, seconds)| *seconds > 0.0)
+                .map(|(name, seconds)| ProjectTotal { name, seconds })
+                .collect(),
+        ),
+    }
+}
+
+fn sorted(mut projects: Vec<ProjectTotal>) -> Vec<ProjectTotal> {
+    projects.sort_by(|a, b| {
+        b.seconds
+            .total_cmp(&a.seconds)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    projects
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct StatsCache {
+    pub slack_id: String,
+    pub day_key: String,
+    pub days: Vec<DayProjects>,
+}
+
+impl StatsCache {
+    pub fn new(slack_id: &str, day_key: &str) -> Self {
+        Self {
+            slack_id: slack_id.to_string(),
+            day_key: day_key.to_string(),
+            days: Vec::new(),
+        }
+    }
+
+    pub fn load() -> Option<Self> {
+        let raw = fs::read_to_string(Self::path()).ok()?;
+        serde_json::from_str(&raw)
+            .map_err(|err| log::warn!("Ignoring unreadable stats cache: {err}"))
+            .ok()
+    }
+
+    pub fn save(&self) -> Result<(), FlavortimeError> {
+        let raw = serde_json::to_string(self)?;
+        let _lock = DirLock::acquire(&data_dir())?;
+        write_atomic(&Self::path(), raw.as_bytes())
+    }
+
+    pub fn clear() {
+        if let Err(err) = fs::remove_file(Self::path()) {
+            if err.kind() != std::io::ErrorKind::NotFound {
+                log::warn!("Failed to remove stats cache: {err}");
+            }
+        }
+    }
+
+    pub fn matches(&self, slack_id: &str, day_key: &str) -> bool {
+        self.slack_id == slack_id && self.day_key == day_key
+    }
+
+    pub fn get(&self, date: &str) -> Option<&DayProjects> {
+        self.days.iter().find(|day| day.date == date)
+    }
+
+    pub fn record(&mut self, day: DayProjects) {
+        self.days.retain(|cached| cached.date != day.date);
+        self.days.push(day);
+        self.days.sort_by(|a, b| a.date.cmp(&b.date));
+    }
+
+    pub fn retain_last(&mut self, days: usize) {
+        let excess = self.days.len().saturating_sub(days);
+        self.days.drain(..excess);
+    }
+
+    fn path() -> PathBuf {
+        data_dir().join("stats.json")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(date: &str, projects: &[(&str, f64)]) -> DayProjects {
+        day_projects(
+            date,
+            projects
+                .iter()
+                .map(|(name, seconds)| (name.to_string(), *seconds)),
+        )
+    }
+
+    #[test]
+    fn aggregate_totals_projects_and_days() {
+        let days = vec![
+            day(
+                "2026-03-01",
+                &[("api", 3600.0), ("web", 1800.0), ("api", 600.0)],
+            ),
+            day("2026-03-02", &[]),
+            day("2026-03-03", &[("web", 7200.0), ("docs", 0.0)]),
+        ];
+
+        let report = StatsReport::aggregate(StatsRange::Week, &days);
+        assert_eq!(report.start_date, "2026-03-01");
+        assert_eq!(report.end_date, "2026-03-03");
+        assert_eq!(report.total_seconds, 13200.0);
+        assert_eq!(report.daily_average_seconds, 4400.0);
+
+        let names: Vec<&str> = report.projects.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["web", "api"]);
+        assert_eq!(report.projects[1].seconds, 4200.0);
+
+        assert_eq!(report.days[0].top_project.as_deref(), Some("api"));
+        assert_eq!(report.days[1].seconds, 0.0);
+        assert_eq!(report.days[1].top_project, None);
+    }
+}
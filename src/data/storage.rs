@@ -0,0 +1,75 @@
+use crate::error::FlavortimeError;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+pub struct DirLock {
+    _file: File,
+}
+
+impl DirLock {
+    pub fn acquire(dir: &Path) -> Result<Self, FlavortimeError> {
+        fs::create_dir_all(dir)?;
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(dir.join(".lock"))?;
+        file.lock()?;
+        Ok(Self { _file: file })
+    }
+}
+
+pub fn write_atomic(path: &Path, contents: &[u8]) -> Result<(), FlavortimeError> {
+    replace_file(path, contents, false)
+}
+
+pub fn write_atomic_private(path: &Path, contents: &[u8]) -> Result<(), FlavortimeError> {
+    replace_file(path, contents, true)
+}
+
+fn replace_file(path: &Path, contents: &[u8], private: bool) -> Result<(), FlavortimeError> {
+    let temp = temp_path(path);
+    let result = write_and_sync(&temp, contents, private).and_then(|()| fs::rename(&temp, path));
+    if let Err(err) = result {
+        let _ = fs::remove_file(&temp);
+        return Err(err.into());
+    }
+
+    sync_parent(path);
+    Ok(())
+}
+
+fn temp_path(path: &Path) -> PathBuf {
+    let name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("data");
+    path.with_file_name(format!(".{name}.{}.tmp", std::process::id()))
+}
+
+fn write_and_sync(path: &Path, contents: &[u8], private: bool) -> std::io::Result<()> {
+    let mut file = File::create(path)?;
+    #[cfg(unix)]
+    if private {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    }
+    #[cfg(not(unix))]
+    let _ = private;
+    file.write_all(contents)?;
+    file.sync_all()
+}
+
+#[cfg(unix)]
+fn sync_parent(path: &Path) {
+    if let Some(parent) = path.parent() {
+        if let Ok(dir) = File::open(parent) {
+            let _ = dir.sync_all();
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn sync_parent(_path: &Path) {}
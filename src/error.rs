@@ -0,0 +1,142 @@
+use crate::services::ratelimit::{retry_after_secs, unix_now};
+use reqwest::{Response, StatusCode};
+use serde::{ser::SerializeStruct, Serialize, Serializer};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FlavortimeError {
+    Network(String),
+    Unauthorized(String),
+    NotFound(String),
+    RateLimited { retry_after: Option<u64> },
+    InvalidSession,
+    Http { status: u16, message: String },
+    Parse(String),
+    Config(String),
+    Storage(String),
+    SecretStore(String),
+    Lock,
+    InvalidInput(String),
+    NotSignedIn,
+    Updater(String),
+    System(String),
+}
+
+impl FlavortimeError {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Network(_) => "network",
+            Self::Unauthorized(_) => "unauthorized",
+            Self::NotFound(_) => "not_found",
+            Self::RateLimited { .. } => "rate_limited",
+            Self::InvalidSession => "invalid_session",
+            Self::Http { .. } => "http",
+            Self::Parse(_) => "parse",
+            Self::Config(_) => "config",
+            Self::Storage(_) => "storage",
+            Self::SecretStore(_) => "secret_store",
+            Self::Lock => "lock",
+            Self::InvalidInput(_) => "invalid_input",
+            Self::NotSignedIn => "not_signed_in",
+            Self::Updater(_) => "updater",
+            Self::System(_) => "system",
+        }
+    }
+
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Self::Network(_) | Self::RateLimited { .. } => true,
+            Self::Http { status, .. } => *status >= 500,
+            _ => false,
+        }
+    }
+
+    pub fn from_status(status: StatusCode, retry_after: Option<u64>, message: String) -> Self {
+        match status {
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => Self::Unauthorized(message),
+            StatusCode::NOT_FOUND => Self::NotFound(message),
+            StatusCode::TOO_MANY_REQUESTS => Self::RateLimited { retry_after },
+            _ => Self::Http {
+                status: status.as_u16(),
+                message,
+            },
+        }
+    }
+
+    pub async fn from_response(response: Response, context: &str) -> Self {
+        let status = response.status();
+        let retry_after = retry_after_secs(response.headers(), unix_now());
+        let body = response.text().await.unwrap_or_default();
+        let message = format!("{context}: {status} {}", body.trim())
+            .trim_end()
+            .to_string();
+        Self::from_status(status, retry_after, message)
+    }
+}
+
+impl fmt::Display for FlavortimeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Network(message)
+            | Self::Unauthorized(message)
+            | Self::NotFound(message)
+            | Self::Parse(message)
+            | Self::Config(message)
+            | Self::Storage(message)
+            | Self::SecretStore(message)
+            | Self::InvalidInput(message)
+            | Self::Updater(message)
+            | Self::System(message)
+            | Self::Http { message, .. } => f.write_str(message),
+            Self::RateLimited {
+                retry_after: Some(seconds),
+            } => write!(f, "Rate limited, retry after {seconds}s"),
+            Self::RateLimited { retry_after: None } => f.write_str("Rate limited"),
+            Self::InvalidSession => f.write_str("Flavortown rejected the session ID"),
+            Self::Lock => f.write_str("Internal state lock failed"),
+            Self::NotSignedIn => f.write_str("Not logged in with Flavortime"),
+        }
+    }
+}
+
+impl std::error::Error for FlavortimeError {}
+
+impl Serialize for FlavortimeError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("FlavortimeError", 3)?;
+        state.serialize_field("kind", self.kind())?;
+        state.serialize_field("message", &self.to_string())?;
+        match self {
+            Self::RateLimited { retry_after } => {
+                state.serialize_field("retry_after", retry_after)?
+            }
+            Self::Http { status, .. } => state.serialize_field("status", status)?,
+            _ => state.skip_field("retry_after")?,
+        }
+        state.end()
+    }
+}
+
+impl From<reqwest::Error> for FlavortimeError {
+    fn from(err: reqwest::Error) -> Self {
+        if err.is_decode() {
+            Self::Parse(err.to_string())
+        } else if let Some(status) = err.status() {
+            Self::from_status(status, None, err.to_string())
+        } else {
+            Self::Network(err.to_string())
+        }
+    }
+}
+
+impl From<serde_json::Error> for FlavortimeError {
+    fn from(err: serde_json::Error) -> Self {
+        Self::Parse(err.to_string())
+    }
+}
+
+impl From<std::io::Error> for FlavortimeError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Storage(err.to_string())
+    }
+}
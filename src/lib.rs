@@ -2,21 +2,28 @@
 
 mod app;
 mod data;
+mod error;
 mod services;
 #[cfg(target_os = "macos")]
 mod tray;
 
 use app::commands::{
-    check_for_update, close_flavortime_session, close_flavortime_session_for_shutdown,
-    download_update, force_refresh_discord, get_discord_status, get_hackatime_data, get_status,
+    activate_presence_profile, check_for_update, close_flavortime_session,
+    close_flavortime_session_for_shutdown, create_presence_profile, delete_presence_profile,
+    download_update, export_history, force_refresh_discord, get_discord_status, get_hackatime_data,
+    get_history, get_rate_limit_status, get_runtime_config, get_sessions, get_stats, get_status,
     init_discord, login_as_adult, login_with_flavortown_api_key, logout, open_external,
-    refresh_referral_codes, restart_for_update, send_flavortown_heartbeat,
-    set_adult_referral_code, set_app_enabled, set_custom_referral_code, set_launch_at_startup,
+    preview_presence, refresh_history, refresh_referral_codes, refresh_tracking,
+    rename_presence_profile, restart_for_update, send_flavortown_heartbeat,
+    set_adult_referral_code, set_app_enabled, set_custom_referral_code, set_daily_goal,
+    set_history_days, set_launch_at_startup, set_max_snapshot_staleness,
+    set_presence_project_hours, set_presence_template, set_privacy_rules,
     set_selected_referral_code, set_show_referral_code, set_show_time_tracking,
-    update_discord_presence,
+    set_tracking_backend, set_window_mode, update_discord_presence,
 };
+use app::instance::Launch;
 use app::state::AppState;
-use data::runtime::validate_startup_fields;
+use data::runtime::{runtime, validate_startup_fields};
 use std::time::Duration;
 use tauri::Manager;
 use tauri_plugin_autostart::MacosLauncher;
@@ -32,13 +39,35 @@ pub fn run() {
         }
     }
 
+    #[cfg(feature = "mock")]
+    let _mock_server = if std::env::args().any(|arg| arg == "--mock") {
+        match services::mock::MockServer::start(None) {
+            Ok(server) => {
+                server.install_env();
+                Some(server)
+            }
+            Err(err) => {
+                eprintln!("Failed to start mock server: {err}");
+                return;
+            }
+        }
+    } else {
+        None
+    };
+
     if let Err(err) = validate_startup_fields() {
         eprintln!("{err}");
         return;
     }
 
+    let mut instance_guard = match app::instance::acquire() {
+        Launch::Primary(guard) => Some(guard),
+        Launch::Unguarded => None,
+        Launch::Forwarded => return,
+    };
+
     #[cfg(target_os = "macos")]
-    let start_hidden = std::env::args().any(|arg| arg == "--hidden");
+    let start_hidden = !app::instance::wants_window(&std::env::args().skip(1).collect::<Vec<_>>());
 
     let app = tauri::Builder::default()
         .plugin(tauri_plugin_autostart::init(
@@ -69,8 +98,17 @@ pub fn run() {
                 )?;
             }
 
+            for warning in &runtime().warnings {
+                log::warn!("{warning}");
+            }
+
             let state = AppState::new();
             app.manage(state);
+            app::scheduler::start(app.handle());
+
+            if let Some(guard) = instance_guard.take() {
+                app::instance::listen(app.handle(), guard);
+            }
 
             #[cfg(target_os = "macos")]
             tray::setup(app)?;
@@ -98,10 +136,31 @@ pub fn run() {
             set_show_time_tracking,
             set_launch_at_startup,
             set_app_enabled,
+            set_tracking_backend,
+            set_max_snapshot_staleness,
+            set_window_mode,
+            set_presence_project_hours,
+            set_presence_template,
+            preview_presence,
+            set_daily_goal,
+            set_history_days,
+            get_history,
+            refresh_history,
+            get_sessions,
+            get_stats,
+            export_history,
+            set_privacy_rules,
+            create_presence_profile,
+            rename_presence_profile,
+            delete_presence_profile,
+            activate_presence_profile,
+            get_rate_limit_status,
             get_hackatime_data,
+            refresh_tracking,
             refresh_referral_codes,
             init_discord,
             get_discord_status,
+            get_runtime_config,
             force_refresh_discord,
             update_discord_presence,
             set_adult_referral_code,
@@ -115,33 +174,36 @@ pub fn run() {
         .expect("error while building tauri application");
 
     app.run(move |app, event| {
-            if let tauri::RunEvent::ExitRequested { api, .. } = event {
-                let intercept_exit = app
-                    .try_state::<AppState>()
-                    .and_then(|state| {
-                        state.shutdown_requested.lock().ok().map(|mut requested| {
-                            if *requested {
-                                false
-                            } else {
-                                *requested = true;
-                                true
-                            }
-                        })
+        if let tauri::RunEvent::ExitRequested { api, code, .. } = event {
+            if code == Some(tauri::RESTART_EXIT_CODE) {
+                return;
+            }
+            let intercept_exit = app
+                .try_state::<AppState>()
+                .and_then(|state| {
+                    state.shutdown_requested.lock().ok().map(|mut requested| {
+                        if *requested {
+                            false
+                        } else {
+                            *requested = true;
+                            true
+                        }
                     })
-                    .unwrap_or(false);
-
-                if intercept_exit {
-                    api.prevent_exit();
-                    let app_handle = app.clone();
-                    tauri::async_runtime::spawn(async move {
-                        let _ = tokio::time::timeout(
-                            Duration::from_secs(3),
-                            close_flavortime_session_for_shutdown(&app_handle),
-                        )
-                        .await;
-                        app_handle.exit(0);
-                    });
-                }
+                })
+                .unwrap_or(false);
+
+            if intercept_exit {
+                api.prevent_exit();
+                let app_handle = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    let _ = tokio::time::timeout(
+                        Duration::from_secs(3),
+                        close_flavortime_session_for_shutdown(&app_handle),
+                    )
+                    .await;
+                    app_handle.exit(0);
+                });
             }
-        });
+        }
+    });
 }
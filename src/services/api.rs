@@ -0,0 +1,440 @@
+use super::flavortown::{self, CloseOutcome, FlavortownUser, HeartbeatOutcome, SessionMetadata};
+use super::hackatime::{CodingBlock, Hackatime, ProjectDetails};
+use super::http::HttpClient;
+use super::pyramid;
+use super::ratelimit::SingleFlight;
+use super::wakatime::WakaTime;
+use crate::data::{
+    config::{Referral, TrackingBackend},
+    runtime::runtime,
+};
+use crate::error::FlavortimeError;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, RwLock};
+
+pub type ApiFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, FlavortimeError>> + Send + 'a>>;
+
+pub trait HackatimeApi: Send + Sync {
+    fn user_projects_details<'a>(
+        &'a self,
+        username: &'a str,
+        start_date: Option<&'a str>,
+        end_date: Option<&'a str>,
+    ) -> ApiFuture<'a, Vec<ProjectDetails>>;
+
+    // Only backends that know when the time was spent have blocks; the rest
+    // answer `None` and callers fall back to totals.
+    fn coding_blocks<'a>(
+        &'a self,
+        _start_date: &'a str,
+        _end_date: &'a str,
+    ) -> ApiFuture<'a, Option<Vec<CodingBlock>>> {
+        Box::pin(async { Ok(None) })
+    }
+}
+
+pub trait ReferralApi: Send + Sync {
+    fn fetch_codes<'a>(&'a self, slack_id: &'a str) -> ApiFuture<'a, Vec<Referral>>;
+}
+
+pub trait FlavortownApi: Send + Sync {
+    fn current_user<'a>(&'a self, api_key: &'a str) -> ApiFuture<'a, FlavortownUser>;
+
+    fn create_session<'a>(
+        &'a self,
+        api_key: &'a str,
+        metadata: &'a SessionMetadata,
+    ) -> ApiFuture<'a, String>;
+
+    fn send_heartbeat<'a>(
+        &'a self,
+        api_key: &'a str,
+        session_id: &'a str,
+        sharing_active_seconds_total: u64,
+        metadata: &'a SessionMetadata,
+    ) -> ApiFuture<'a, HeartbeatOutcome>;
+
+    fn close_session<'a>(
+        &'a self,
+        api_key: &'a str,
+        session_id: &'a str,
+        sharing_active_seconds_total: u64,
+        metadata: &'a SessionMetadata,
+    ) -> ApiFuture<'a, CloseOutcome>;
+}
+
+pub struct TrackingSelector {
+    current: RwLock<Arc<dyn HackatimeApi>>,
+    flights: SingleFlight<Vec<ProjectDetails>>,
+}
+
+impl TrackingSelector {
+    pub fn new(api: Arc<dyn HackatimeApi>) -> Self {
+        Self {
+            current: RwLock::new(api),
+            flights: SingleFlight::default(),
+        }
+    }
+
+    pub fn select(&self, api: Arc<dyn HackatimeApi>) {
+        *self.current.write().unwrap_or_else(|err| err.into_inner()) = api;
+    }
+
+    fn current(&self) -> Arc<dyn HackatimeApi> {
+        self.current
+            .read()
+            .unwrap_or_else(|err| err.into_inner())
+            .clone()
+    }
+}
+
+impl HackatimeApi for TrackingSelector {
+    fn user_projects_details<'a>(
+        &'a self,
+        username: &'a str,
+        start_date: Option<&'a str>,
+        end_date: Option<&'a str>,
+    ) -> ApiFuture<'a, Vec<ProjectDetails>> {
+        let api = self.current();
+        let key = format!(
+            "{:p}|{username}|{}|{}",
+            Arc::as_ptr(&api),
+            start_date.unwrap_or_default(),
+            end_date.unwrap_or_default()
+        );
+        Box::pin(self.flights.run(key, move || async move {
+            api.user_projects_details(username, start_date, end_date)
+                .await
+        }))
+    }
+
+    fn coding_blocks<'a>(
+        &'a self,
+        start_date: &'a str,
+        end_date: &'a str,
+    ) -> ApiFuture<'a, Option<Vec<CodingBlock>>> {
+        let api = self.current();
+        Box::pin(async move { api.coding_blocks(start_date, end_date).await })
+    }
+}
+
+#[derive(Clone)]
+pub struct Services {
+    pub http: HttpClient,
+    pub hackatime: Arc<TrackingSelector>,
+    pub referrals: Arc<dyn ReferralApi>,
+    pub flavortown: Arc<dyn FlavortownApi>,
+}
+
+impl Services {
+    pub fn from_runtime(http: &HttpClient) -> Self {
+        let rt = runtime();
+        Self {
+            http: http.clone(),
+            hackatime: Arc::new(TrackingSelector::new(Arc::new(ReqwestHackatime::new(
+                http.clone(),
+                &rt.hackatime_base_url,
+            )))),
+            referrals: Arc::new(ReqwestReferrals::new(
+                http.clone(),
+                &rt.pyramid_base_url,
+                &rt.flavortown_campaign_slug,
+            )),
+            flavortown: Arc::new(ReqwestFlavortown::new(
+                http.clone(),
+                &rt.flavortown_base_url,
+            )),
+        }
+    }
+
+    pub fn select_tracking(
+        &self,
+        backend: &TrackingBackend,
+        wakatime_base_url: Option<&str>,
+        wakatime_api_key: Option<&str>,
+    ) -> Result<(), FlavortimeError> {
+        let api: Arc<dyn HackatimeApi> = match backend {
+            TrackingBackend::Hackatime => Arc::new(ReqwestHackatime::new(
+                self.http.clone(),
+                &runtime().hackatime_base_url,
+            )),
+            TrackingBackend::WakaTime => {
+                let base_url = wakatime_base_url.ok_or_else(|| {
+                    FlavortimeError::InvalidInput("Missing WakaTime server URL".to_string())
+                })?;
+                let api_key = wakatime_api_key.ok_or_else(|| {
+                    FlavortimeError::InvalidInput("Missing WakaTime API key".to_string())
+                })?;
+                Arc::new(WakaTime::new(self.http.clone(), base_url, api_key))
+            }
+        };
+        self.hackatime.select(api);
+        Ok(())
+    }
+}
+
+pub struct ReqwestHackatime {
+    http: HttpClient,
+    base_url: String,
+}
+
+impl ReqwestHackatime {
+    pub fn new(http: HttpClient, base_url: &str) -> Self {
+        Self {
+            http,
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+}
+
+impl HackatimeApi for ReqwestHackatime {
+    fn user_projects_details<'a>(
+        &'a self,
+        username: &'a str,
+        start_date: Option<&'a str>,
+        end_date: Option<&'a str>,
+    ) -> ApiFuture<'a, Vec<ProjectDetails>> {
+        Box::pin(Hackatime::user_projects_details(
+            &self.http,
+            &self.base_url,
+            username,
+            start_date,
+            end_date,
+        ))
+    }
+}
+
+pub struct ReqwestReferrals {
+    http: HttpClient,
+    base_url: String,
+    campaign_slug: String,
+}
+
+impl ReqwestReferrals {
+    pub fn new(http: HttpClient, base_url: &str, campaign_slug: &str) -> Self {
+        Self {
+            http,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            campaign_slug: campaign_slug.to_string(),
+        }
+    }
+}
+
+impl ReferralApi for ReqwestReferrals {
+    fn fetch_codes<'a>(&'a self, slack_id: &'a str) -> ApiFuture<'a, Vec<Referral>> {
+        Box::pin(pyramid::fetch_codes(
+            &self.http,
+            &self.base_url,
+            &self.campaign_slug,
+            slack_id,
+        ))
+    }
+}
+
+pub struct ReqwestFlavortown {
+    http: HttpClient,
+    base_url: String,
+}
+
+impl ReqwestFlavortown {
+    pub fn new(http: HttpClient, base_url: &str) -> Self {
+        Self {
+            http,
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+}
+
+impl FlavortownApi for ReqwestFlavortown {
+    fn current_user<'a>(&'a self, api_key: &'a str) -> ApiFuture<'a, FlavortownUser> {
+        Box::pin(flavortown::current_user(
+            &self.http,
+            &self.base_url,
+            api_key,
+        ))
+    }
+
+    fn create_session<'a>(
+        &'a self,
+        api_key: &'a str,
+        metadata: &'a SessionMetadata,
+    ) -> ApiFuture<'a, String> {
+        Box::pin(flavortown::create_session(
+            &self.http,
+            &self.base_url,
+            api_key,
+            metadata.platform,
+            metadata.app_version,
+        ))
+    }
+
+    fn send_heartbeat<'a>(
+        &'a self,
+        api_key: &'a str,
+        session_id: &'a str,
+        sharing_active_seconds_total: u64,
+        metadata: &'a SessionMetadata,
+    ) -> ApiFuture<'a, HeartbeatOutcome> {
+        Box::pin(flavortown::send_heartbeat(
+            &self.http,
+            &self.base_url,
+            api_key,
+            session_id,
+            sharing_active_seconds_total,
+            metadata.platform,
+            metadata.app_version,
+        ))
+    }
+
+    fn close_session<'a>(
+        &'a self,
+        api_key: &'a str,
+        session_id: &'a str,
+        sharing_active_seconds_total: u64,
+        metadata: &'a SessionMetadata,
+    ) -> ApiFuture<'a, CloseOutcome> {
+        Box::pin(flavortown::close_session(
+            &self.http,
+            &self.base_url,
+            api_key,
+            session_id,
+            sharing_active_seconds_total,
+            metadata.platform,
+            metadata.app_version,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::hackatime::latest_project;
+    use crate::services::mock::{
+        test_server, MOCK_RATE_LIMITED, MOCK_SLACK_ID, MOCK_SLOW, MOCK_UNAUTHORIZED,
+        MOCK_UNKNOWN_USER, MOCK_WAKATIME_KEY, RETRY_AFTER_SECS, SESSION_HEARTBEATS,
+        TEST_SLOW_DELAY,
+    };
+    use tauri::async_runtime::block_on;
+
+    const METADATA: SessionMetadata = SessionMetadata {
+        platform: "test",
+        app_version: "0.0.0",
+    };
+
+    fn flavortown() -> ReqwestFlavortown {
+        ReqwestFlavortown::new(HttpClient::new(None), &test_server().base_url())
+    }
+
+    #[test]
+    fn login_returns_mock_slack_id() {
+        let user = block_on(flavortown().current_user("any-key")).expect("login");
+        assert_eq!(user.slack_id, MOCK_SLACK_ID);
+    }
+
+    #[test]
+    fn unauthorized_key_is_rejected() {
+        let err = block_on(flavortown().current_user(MOCK_UNAUTHORIZED))
+            .err()
+            .expect("unauthorized");
+        assert!(matches!(err, FlavortimeError::Unauthorized(_)));
+    }
+
+    #[test]
+    fn rate_limited_key_reports_retry_after() {
+        let err = block_on(flavortown().current_user(MOCK_RATE_LIMITED))
+            .err()
+            .expect("rate limited");
+        assert_eq!(
+            err,
+            FlavortimeError::RateLimited {
+                retry_after: Some(RETRY_AFTER_SECS)
+            }
+        );
+    }
+
+    #[test]
+    fn slow_responses_are_delayed() {
+        let started = std::time::Instant::now();
+        block_on(flavortown().current_user(MOCK_SLOW)).expect("slow login");
+        assert!(started.elapsed() >= TEST_SLOW_DELAY);
+    }
+
+    #[test]
+    fn session_expires_after_heartbeats() {
+        let api = flavortown();
+        let session_id = block_on(api.create_session("key", &METADATA)).expect("session");
+
+        for _ in 0..SESSION_HEARTBEATS {
+            let outcome =
+                block_on(api.send_heartbeat("key", &session_id, 60, &METADATA)).expect("heartbeat");
+            assert!(matches!(outcome, HeartbeatOutcome::ActiveUsers(_)));
+        }
+
+        let outcome = block_on(api.send_heartbeat("key", &session_id, 60, &METADATA))
+            .expect("expired heartbeat");
+        assert!(matches!(outcome, HeartbeatOutcome::InvalidSessionId));
+
+        let outcome =
+            block_on(api.close_session("key", &session_id, 60, &METADATA)).expect("close");
+        assert!(matches!(outcome, CloseOutcome::InvalidSessionId));
+    }
+
+    #[test]
+    fn projects_fixture_has_latest_project() {
+        let api = ReqwestHackatime::new(HttpClient::new(None), &test_server().base_url());
+        let projects =
+            block_on(api.user_projects_details(MOCK_SLACK_ID, None, None)).expect("projects");
+        let (name, _) = latest_project(&projects, None).expect("latest project");
+        assert_eq!(name, "flavortime");
+    }
+
+    #[test]
+    fn tracking_selector_switches_backends() {
+        let names = |selector: &TrackingSelector| {
+            let projects = block_on(selector.user_projects_details(
+                MOCK_SLACK_ID,
+                Some("2026-03-01T00:00:00Z"),
+                Some("2026-03-02T00:00:00Z"),
+            ))
+            .expect("projects");
+            let mut names: Vec<String> = projects.into_iter().map(|p| p.name).collect();
+            names.sort();
+            names
+        };
+        let hackatime = Arc::new(ReqwestHackatime::new(
+            HttpClient::new(None),
+            &test_server().base_url(),
+        ));
+        let selector = TrackingSelector::new(hackatime.clone());
+        assert_eq!(
+            names(&selector),
+            ["dotfiles", "flavortime", "pyramid-scheme"]
+        );
+
+        let base_url = format!("{}/api/v1", test_server().base_url());
+        selector.select(Arc::new(WakaTime::new(
+            HttpClient::new(None),
+            &base_url,
+            MOCK_WAKATIME_KEY,
+        )));
+        assert_eq!(names(&selector), ["dotfiles", "flavortime"]);
+
+        selector.select(hackatime);
+        assert_eq!(names(&selector).len(), 3);
+    }
+
+    #[test]
+    fn referral_codes_are_served() {
+        let api = ReqwestReferrals::new(
+            HttpClient::new(None),
+            &test_server().base_url(),
+            "flavortown",
+        );
+        let codes = block_on(api.fetch_codes(MOCK_SLACK_ID)).expect("codes");
+        assert_eq!(codes.len(), 2);
+
+        let unknown = block_on(api.fetch_codes(MOCK_UNKNOWN_USER)).expect("unknown user");
+        assert!(unknown.is_empty());
+    }
+}
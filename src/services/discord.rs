@@ -1,4 +1,5 @@
-use crate::data::locale::{rich_presence_text, RichPresenceText};
+use super::presence::{self, PresenceValues, RenderedPresence};
+use crate::data::config::{PresenceTemplate, ProjectHoursSource};
 use discord_presence::{
     models::{ActivityType, DisplayType},
     Client, Event,
@@ -13,15 +14,32 @@ use std::{
     time::{SystemTime, UNIX_EPOCH},
 };
 
+#[derive(Clone, Default)]
+pub struct PresenceOptions {
+    pub rolling_window: bool,
+    pub project_hours: ProjectHoursSource,
+    pub template: PresenceTemplate,
+    pub daily_goal_minutes: u64,
+}
+
+#[derive(Clone, Default)]
+pub struct Activity {
+    pub project: Option<String>,
+    pub language: Option<String>,
+    pub project_hours: Option<f64>,
+    pub hours: Option<f64>,
+    pub streak: Option<u32>,
+}
+
 #[derive(Default)]
 struct Presence {
-    project: Option<String>,
-    hours: Option<f64>,
+    activity: Activity,
     referral: Option<String>,
     show_referral_button: bool,
     enabled: bool,
     session_start: Option<u64>,
     activity_published: bool,
+    options: PresenceOptions,
 }
 
 pub struct DiscordPresenceManager {
@@ -105,15 +123,17 @@ impl DiscordPresenceManager {
         self.client.start();
     }
 
+    pub fn set_options(&mut self, options: PresenceOptions) {
+        self.state.options = options;
+    }
+
     pub fn update(
         &mut self,
-        project: Option<String>,
-        hours: Option<f64>,
+        activity: Activity,
         referral: Option<String>,
         show_referral_button: bool,
     ) {
-        self.state.project = project;
-        self.state.hours = hours;
+        self.state.activity = activity;
         self.state.referral = referral;
         self.state.show_referral_button = show_referral_button;
         if self.has_activity_payload() {
@@ -153,6 +173,12 @@ impl DiscordPresenceManager {
         });
     }
 
+    pub fn session_length(&self) -> Option<u64> {
+        self.state
+            .session_start
+            .map(|start| unix_secs().saturating_sub(start))
+    }
+
     pub fn is_ready(&self) -> bool {
         self.ready.load(Ordering::Relaxed)
     }
@@ -227,64 +253,29 @@ impl DiscordPresenceManager {
             return;
         }
 
-        let text = rich_presence_text();
-        let referral_host = non_empty_trimmed(Some(text.referral_host.as_str()))
-            .unwrap_or("flavortown.hackclub.com");
-        let referral_code = non_empty_trimmed(self.state.referral.as_deref());
-        let referral_url = match referral_code {
-            Some(code) => format!("https://{referral_host}/{code}"),
-            None => format!("https://{referral_host}"),
-        };
-        let project_line = if let Some(name) = self
-            .state
-            .project
-            .as_deref()
-            .map(str::trim)
-            .filter(|value| !value.is_empty())
-        {
-            if let Some(hours) = self.state.hours.filter(|hours| *hours > 0.0) {
-                let time = fmt_hours_short(hours);
-                format!("{}{name} ({time} spent)", text.details_project_prefix)
-            } else {
-                format!("{}{name}", text.details_project_prefix)
-            }
-        } else {
-            text.details_idle.clone()
-        };
-
-        let time_line = self
-            .state
-            .hours
-            .filter(|hours| *hours > 0.0)
-            .map(|hours| fmt_hours(hours, text));
-        let status_tagline = text.status_tagline.trim();
-        let details = if status_tagline.is_empty() {
-            text.details_idle.clone()
-        } else {
-            status_tagline.to_string()
-        };
-        let state_line = Some(project_line.clone());
+        let referral_url = presence::referral_url(self.state.referral.as_deref());
         let session_start = self.state.session_start;
         let show_referral_button = self.state.show_referral_button;
-        let brand_label = text.brand_label.clone();
-        let referral_button = text.referral_button.clone();
-        let small_text = if status_tagline.is_empty() {
-            None
-        } else {
-            time_line
-        };
+        let RenderedPresence {
+            details,
+            state: state_line,
+            large_text,
+            small_text,
+            button_label,
+        } = self.render();
 
         let mut client = self.client.clone();
         spawn_named("discord-rpc-activity", move || {
             let result = client.set_activity(|activity| {
                 let mut next = activity
-                    .details(details)
                     .activity_type(ActivityType::Playing)
                     .status_display(DisplayType::Name)
                     .assets(|assets| {
-                        let mut next = assets
-                            .large_image("flavortown_logo")
-                            .large_text(brand_label.clone());
+                        let mut next = assets.large_image("flavortown_logo");
+
+                        if let Some(line) = large_text.as_ref() {
+                            next = next.large_text(line.clone());
+                        }
 
                         if let Some(line) = small_text.as_ref() {
                             next = next.small_image("flavortown_logo").small_text(line.clone());
@@ -293,6 +284,10 @@ impl DiscordPresenceManager {
                         next
                     });
 
+                if let Some(line) = details.as_ref() {
+                    next = next.details(line.clone());
+                }
+
                 if let Some(line) = state_line.as_ref() {
                     next = next.state(line.clone());
                 }
@@ -301,11 +296,9 @@ impl DiscordPresenceManager {
                     next = next.timestamps(|ts| ts.start(start));
                 }
 
-                if show_referral_button {
+                if let Some(label) = button_label.as_ref().filter(|_| show_referral_button) {
                     next = next.append_buttons(|button| {
-                        button
-                            .label(referral_button.clone())
-                            .url(referral_url.clone())
+                        button.label(label.clone()).url(referral_url.clone())
                     });
                 }
 
@@ -330,50 +323,48 @@ impl DiscordPresenceManager {
     }
 
     fn has_activity_payload(&self) -> bool {
-        let has_tagline = !rich_presence_text().status_tagline.trim().is_empty();
-        let has_project = non_empty_trimmed(self.state.project.as_deref()).is_some();
-        let has_hours = self.state.hours.is_some_and(|value| value > 0.0);
-        let has_referral = self.state.show_referral_button;
-
-        has_tagline || has_project || has_hours || has_referral
+        self.state.show_referral_button || !self.render().is_empty()
     }
-}
 
-impl Drop for DiscordPresenceManager {
-    fn drop(&mut self) {
-        self.stop();
+    fn render(&self) -> RenderedPresence {
+        render_presence(
+            &self.state.options,
+            &self.state.activity,
+            self.state.referral.clone(),
+            self.session_length(),
+        )
     }
 }
 
-fn fmt_hours_short(hours: f64) -> String {
-    let (whole, rem) = split_hours_minutes(hours);
-    if whole > 0 {
-        format!("{whole}h {rem}m")
-    } else {
-        format!("{rem}m")
-    }
+pub fn render_presence(
+    options: &PresenceOptions,
+    activity: &Activity,
+    referral: Option<String>,
+    session_length: Option<u64>,
+) -> RenderedPresence {
+    let values = PresenceValues {
+        project: activity.project.clone(),
+        project_hours: match options.project_hours {
+            ProjectHoursSource::Project => activity.project_hours,
+            ProjectHoursSource::Total => activity.hours,
+        },
+        today: activity.hours,
+        session_length,
+        referral_code: referral,
+        language: activity.language.clone(),
+        rolling_window: options.rolling_window,
+        streak: activity.streak,
+        daily_goal: Some(options.daily_goal_minutes as f64 / 60.0),
+    };
+    presence::render(&options.template, &values)
 }
 
-fn fmt_hours(hours: f64, text: &RichPresenceText) -> String {
-    let (whole, rem) = split_hours_minutes(hours);
-    if whole > 0 {
-        format!(
-            "{}{whole}h {rem}m{}",
-            text.time_today_prefix, text.time_logged_suffix
-        )
-    } else {
-        format!(
-            "{}{rem}m{}",
-            text.time_today_prefix, text.time_logged_suffix
-        )
+impl Drop for DiscordPresenceManager {
+    fn drop(&mut self) {
+        self.stop();
     }
 }
 
-fn split_hours_minutes(hours: f64) -> (u32, u32) {
-    let mins = (hours * 60.0).round().max(0.0) as u32;
-    (mins / 60, mins % 60)
-}
-
 fn spawn_named(name: &str, task: impl FnOnce() + Send + 'static) {
     if let Err(err) = Builder::new().name(name.to_string()).spawn(task) {
         log::error!("Failed to spawn thread `{name}`: {}", err);
@@ -386,10 +377,6 @@ fn is_transient_rpc_error(event: &str) -> bool {
         || event.contains("missing field `cmd`")
 }
 
-fn non_empty_trimmed(value: Option<&str>) -> Option<&str> {
-    value.map(str::trim).filter(|value| !value.is_empty())
-}
-
 fn unix_secs() -> u64 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -1,4 +1,5 @@
-use crate::data::runtime::runtime;
+use super::http::{HttpClient, DEFAULT_TIMEOUT, HEARTBEAT_TIMEOUT};
+use crate::error::FlavortimeError;
 use reqwest::StatusCode;
 use serde::Deserialize;
 use serde_json::json;
@@ -51,87 +52,79 @@ pub fn session_metadata() -> SessionMetadata {
     }
 }
 
-pub async fn current_user(api_key: &str) -> Result<FlavortownUser, String> {
+pub async fn current_user(
+    http: &HttpClient,
+    base_url: &str,
+    api_key: &str,
+) -> Result<FlavortownUser, FlavortimeError> {
     let api_key = api_key.trim();
-    let url = format!("{}/api/v1/users/me", runtime().flavortown_base_url);
-    let response = reqwest::Client::new()
+    let url = format!("{base_url}/api/v1/users/me");
+    let request = http
         .get(url)
-        .header("Authorization", format!("Bearer {api_key}"))
-        .send()
-        .await
-        .map_err(|err| err.to_string())?;
+        .header("Authorization", format!("Bearer {api_key}"));
+    let response = http.send(request, DEFAULT_TIMEOUT).await?;
 
     if !response.status().is_success() {
-        let status = response.status();
-        let body = response.text().await.unwrap_or_default();
-        return Err(format!("Flavortown login failed: {status} {body}"));
+        return Err(FlavortimeError::from_response(response, "Flavortown login failed").await);
     }
 
-    let body = response
-        .json::<MeResponse>()
-        .await
-        .map_err(|err| err.to_string())?;
+    let body = response.json::<MeResponse>().await?;
     let slack_id = body
         .slack_id
         .map(|value| value.trim().to_owned())
         .filter(|value| !value.is_empty())
-        .ok_or_else(|| "No Slack ID associated with this Flavortown account".to_string())?;
+        .ok_or_else(|| {
+            FlavortimeError::NotFound(
+                "No Slack ID associated with this Flavortown account".to_string(),
+            )
+        })?;
 
     Ok(FlavortownUser { slack_id })
 }
 
 pub async fn create_session(
+    http: &HttpClient,
+    base_url: &str,
     api_key: &str,
     platform: &str,
     app_version: &str,
-) -> Result<String, String> {
+) -> Result<String, FlavortimeError> {
     let api_key = api_key.trim();
-    let url = format!(
-        "{}/api/v1/flavortime/session",
-        runtime().flavortown_base_url
-    );
+    let url = format!("{base_url}/api/v1/flavortime/session");
     let payload = json!({
         "platform": platform,
         "app_version": app_version
     });
 
-    let response = reqwest::Client::new()
+    let request = http
         .post(url)
         .header("Authorization", format!("Bearer {api_key}"))
-        .json(&payload)
-        .send()
-        .await
-        .map_err(|err| err.to_string())?;
+        .json(&payload);
+    let response = http.send(request, DEFAULT_TIMEOUT).await?;
 
     if !response.status().is_success() {
-        let status = response.status();
-        let body = response.text().await.unwrap_or_default();
-        return Err(format!("Session creation failed: {status} {body}"));
+        return Err(FlavortimeError::from_response(response, "Session creation failed").await);
     }
 
-    let body = response
-        .json::<SessionIdResponse>()
-        .await
-        .map_err(|err| err.to_string())?;
+    let body = response.json::<SessionIdResponse>().await?;
 
     body.session_id
         .map(|value| value.trim().to_owned())
         .filter(|value| !value.is_empty())
-        .ok_or_else(|| "Flavortown did not return a session ID".to_string())
+        .ok_or_else(|| FlavortimeError::Parse("Flavortown did not return a session ID".to_string()))
 }
 
 pub async fn send_heartbeat(
+    http: &HttpClient,
+    base_url: &str,
     api_key: &str,
     session_id: &str,
     sharing_active_seconds_total: u64,
     platform: &str,
     app_version: &str,
-) -> Result<HeartbeatOutcome, String> {
+) -> Result<HeartbeatOutcome, FlavortimeError> {
     let api_key = api_key.trim();
-    let url = format!(
-        "{}/api/v1/flavortime/heartbeat",
-        runtime().flavortown_base_url
-    );
+    let url = format!("{base_url}/api/v1/flavortime/heartbeat");
     let payload = json!({
         "session_id": session_id,
         "sharing_active_seconds_total": sharing_active_seconds_total,
@@ -139,29 +132,22 @@ pub async fn send_heartbeat(
         "app_version": app_version
     });
 
-    let response = reqwest::Client::new()
+    let request = http
         .post(url)
         .header("Authorization", format!("Bearer {api_key}"))
         .header("X-Flavortime-Session-Id", session_id)
-        .json(&payload)
-        .send()
-        .await
-        .map_err(|err| err.to_string())?;
+        .json(&payload);
+    let response = http.send(request, HEARTBEAT_TIMEOUT).await?;
 
     if !response.status().is_success() {
         if response.status() == StatusCode::NOT_FOUND {
             return Ok(HeartbeatOutcome::InvalidSessionId);
         }
 
-        let status = response.status();
-        let body = response.text().await.unwrap_or_default();
-        return Err(format!("Heartbeat failed: {status} {body}"));
+        return Err(FlavortimeError::from_response(response, "Heartbeat failed").await);
     }
 
-    let body = response
-        .json::<HeartbeatResponse>()
-        .await
-        .map_err(|err| err.to_string())?;
+    let body = response.json::<HeartbeatResponse>().await?;
 
     Ok(HeartbeatOutcome::ActiveUsers(
         body.active_users.unwrap_or(0),
@@ -169,14 +155,16 @@ pub async fn send_heartbeat(
 }
 
 pub async fn close_session(
+    http: &HttpClient,
+    base_url: &str,
     api_key: &str,
     session_id: &str,
     sharing_active_seconds_total: u64,
     platform: &str,
     app_version: &str,
-) -> Result<CloseOutcome, String> {
+) -> Result<CloseOutcome, FlavortimeError> {
     let api_key = api_key.trim();
-    let url = format!("{}/api/v1/flavortime/close", runtime().flavortown_base_url);
+    let url = format!("{base_url}/api/v1/flavortime/close");
     let payload = json!({
         "session_id": session_id,
         "sharing_active_seconds_total": sharing_active_seconds_total,
@@ -184,29 +172,22 @@ pub async fn close_session(
         "app_version": app_version
     });
 
-    let response = reqwest::Client::new()
+    let request = http
         .post(url)
         .header("Authorization", format!("Bearer {api_key}"))
         .header("X-Flavortime-Session-Id", session_id)
-        .json(&payload)
-        .send()
-        .await
-        .map_err(|err| err.to_string())?;
+        .json(&payload);
+    let response = http.send(request, HEARTBEAT_TIMEOUT).await?;
 
     if !response.status().is_success() {
         if response.status() == StatusCode::NOT_FOUND {
             return Ok(CloseOutcome::InvalidSessionId);
         }
 
-        let status = response.status();
-        let body = response.text().await.unwrap_or_default();
-        return Err(format!("Close session failed: {status} {body}"));
+        return Err(FlavortimeError::from_response(response, "Close session failed").await);
     }
 
-    let body = response
-        .json::<CloseResponse>()
-        .await
-        .map_err(|err| err.to_string())?;
+    let body = response.json::<CloseResponse>().await?;
 
     Ok(CloseOutcome::ActiveUsers(body.active_users.unwrap_or(0)))
 }
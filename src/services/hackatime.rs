@@ -1,8 +1,20 @@
-use crate::data::runtime::runtime;
-use chrono::{DateTime, Duration as ChronoDuration, SecondsFormat, Utc};
-use serde::Deserialize;
+use super::http::{HttpClient, DEFAULT_TIMEOUT};
+use crate::data::config::WindowMode;
+use crate::error::FlavortimeError;
+use chrono::{
+    DateTime, Days, Duration as ChronoDuration, FixedOffset, Local, NaiveDate, SecondsFormat,
+    TimeZone, Timelike, Utc,
+};
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Deserialize)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct CodingBlock {
+    pub project: String,
+    pub start_unix: i64,
+    pub end_unix: i64,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct ProjectDetails {
     pub name: String,
     #[serde(default)]
@@ -11,6 +23,16 @@ pub struct ProjectDetails {
     pub last_heartbeat: Option<String>,
     #[serde(default)]
     pub most_recent_heartbeat: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_languages")]
+    pub languages: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum LanguageEntry {
+    Name(String),
+    Named { name: String },
+    Other(serde::de::IgnoredAny),
 }
 
 #[derive(Deserialize)]
@@ -21,24 +43,114 @@ struct ProjectsDetailsResponse {
 
 pub struct Hackatime;
 
-pub struct RollingWindow {
+pub struct TrackingWindow {
     pub start_rfc3339: String,
     pub end_rfc3339: String,
     pub start_unix: u64,
 }
 
-pub fn rolling_24h_window() -> RollingWindow {
-    let end = Utc::now();
-    let start = end - ChronoDuration::hours(24);
+pub fn tracking_window(mode: &WindowMode) -> TrackingWindow {
+    tracking_window_at(mode, Utc::now())
+}
+
+pub fn tracking_window_at(mode: &WindowMode, now: DateTime<Utc>) -> TrackingWindow {
+    let end = now
+        .with_nanosecond(0)
+        .and_then(|now| now.with_second(0))
+        .unwrap_or(now);
+    let rolling_start = end - ChronoDuration::hours(24);
+    let start = match mode {
+        WindowMode::Rolling24h => None,
+        WindowMode::LocalDay => day_start(&end.with_timezone(&Local), 0),
+        WindowMode::Custom {
+            utc_offset_minutes,
+            day_start_hour,
+        } => FixedOffset::east_opt(utc_offset_minutes.saturating_mul(60))
+            .and_then(|offset| day_start(&end.with_timezone(&offset), *day_start_hour)),
+    }
+    .unwrap_or(rolling_start);
     let start_unix = u64::try_from(start.timestamp()).unwrap_or_default();
 
-    RollingWindow {
+    TrackingWindow {
         start_rfc3339: start.to_rfc3339_opts(SecondsFormat::Secs, true),
         end_rfc3339: end.to_rfc3339_opts(SecondsFormat::Secs, true),
         start_unix,
     }
 }
 
+pub struct DayWindow {
+    pub date: String,
+    pub start_rfc3339: String,
+    pub end_rfc3339: String,
+}
+
+pub fn day_windows(mode: &WindowMode, days: u32) -> Vec<DayWindow> {
+    day_windows_at(mode, days, Utc::now())
+}
+
+pub fn day_windows_at(mode: &WindowMode, days: u32, now: DateTime<Utc>) -> Vec<DayWindow> {
+    let now = now
+        .with_nanosecond(0)
+        .and_then(|now| now.with_second(0))
+        .unwrap_or(now);
+    match mode {
+        WindowMode::Custom {
+            utc_offset_minutes,
+            day_start_hour,
+        } if *day_start_hour < 24 => {
+            match FixedOffset::east_opt(utc_offset_minutes.saturating_mul(60)) {
+                Some(offset) => windows_in(&now.with_timezone(&offset), *day_start_hour, days),
+                None => windows_in(&now.with_timezone(&Local), 0, days),
+            }
+        }
+        _ => windows_in(&now.with_timezone(&Local), 0, days),
+    }
+}
+
+fn windows_in<Tz: TimeZone>(now: &DateTime<Tz>, hour: u32, days: u32) -> Vec<DayWindow> {
+    let timezone = now.timezone();
+    let start_on = |date: NaiveDate| {
+        date.and_hms_opt(hour, 0, 0)
+            .and_then(|naive| timezone.from_local_datetime(&naive).earliest())
+            .map(|start| start.with_timezone(&Utc))
+    };
+    let Some(today) = day_start(now, hour).map(|start| start.with_timezone(&timezone).date_naive())
+    else {
+        return Vec::new();
+    };
+    let now = now.with_timezone(&Utc);
+
+    (0..days)
+        .rev()
+        .filter_map(|back| {
+            let date = today.checked_sub_days(Days::new(u64::from(back)))?;
+            let start = start_on(date)?;
+            let end = start_on(date.succ_opt()?)?.min(now);
+            Some(DayWindow {
+                date: date.format("%Y-%m-%d").to_string(),
+                start_rfc3339: start.to_rfc3339_opts(SecondsFormat::Secs, true),
+                end_rfc3339: end.to_rfc3339_opts(SecondsFormat::Secs, true),
+            })
+        })
+        .collect()
+}
+
+fn day_start<Tz: TimeZone>(now: &DateTime<Tz>, hour: u32) -> Option<DateTime<Utc>> {
+    let timezone = now.timezone();
+    let today = now.date_naive();
+    let start_on = |date: chrono::NaiveDate| {
+        date.and_hms_opt(hour, 0, 0)
+            .and_then(|naive| timezone.from_local_datetime(&naive).earliest())
+    };
+
+    let start = start_on(today)?;
+    if start <= *now {
+        Some(start.with_timezone(&Utc))
+    } else {
+        start_on(today.pred_opt()?).map(|start| start.with_timezone(&Utc))
+    }
+}
+
 pub fn latest_project(
     projects: &[ProjectDetails],
     min_timestamp: Option<u64>,
@@ -70,6 +182,19 @@ fn project_timestamp(project: &ProjectDetails) -> Option<u64> {
         })
 }
 
+fn deserialize_languages<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<String>, D::Error> {
+    let entries = Option::<Vec<LanguageEntry>>::deserialize(deserializer)?.unwrap_or_default();
+    Ok(entries
+        .into_iter()
+        .filter_map(|entry| match entry {
+            LanguageEntry::Name(name) | LanguageEntry::Named { name } => Some(name),
+            LanguageEntry::Other(_) => None,
+        })
+        .collect())
+}
+
 fn parse_iso_timestamp(value: &str) -> Option<u64> {
     DateTime::parse_from_rfc3339(value)
         .ok()
@@ -78,21 +203,24 @@ fn parse_iso_timestamp(value: &str) -> Option<u64> {
 
 impl Hackatime {
     pub async fn user_projects_details(
+        http: &HttpClient,
+        base_url: &str,
         username: &str,
         start_date: Option<&str>,
         end_date: Option<&str>,
-    ) -> Result<Vec<ProjectDetails>, String> {
+    ) -> Result<Vec<ProjectDetails>, FlavortimeError> {
         let username = username.trim();
         if username.is_empty() {
-            return Err("Missing Slack ID".to_string());
+            return Err(FlavortimeError::InvalidInput(
+                "Missing Slack ID".to_string(),
+            ));
         }
 
         let mut url = format!(
             "{}/api/v1/users/{}/projects/details",
-            runtime().hackatime_base_url,
+            base_url,
             urlencoding::encode(username)
         );
-        let client = reqwest::Client::new();
 
         let mut query_parts = Vec::<String>::new();
         if let Some(value) = start_date.filter(|value| !value.trim().is_empty()) {
@@ -108,21 +236,71 @@ impl Hackatime {
             url.push_str(&query_parts.join("&"));
         }
 
-        let response = client
-            .get(url)
-            .send()
-            .await
-            .map_err(|err| err.to_string())?;
+        let response = http.send(http.get(url), DEFAULT_TIMEOUT).await?;
         if !response.status().is_success() {
-            let status = response.status();
-            let body = response.text().await.unwrap_or_default();
-            return Err(format!("Failed to get project details: {status} {body}"));
+            return Err(
+                FlavortimeError::from_response(response, "Failed to get project details").await,
+            );
         }
 
-        let body = response
-            .json::<ProjectsDetailsResponse>()
-            .await
-            .map_err(|err| err.to_string())?;
+        let body = response.json::<ProjectsDetailsResponse>().await?;
         Ok(body.projects)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(value: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(value)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn rolling_window_covers_last_24_hours() {
+        let window = tracking_window_at(&WindowMode::Rolling24h, at("2026-03-10T15:42:31Z"));
+        assert_eq!(window.start_rfc3339, "2026-03-09T15:42:00Z");
+        assert_eq!(window.end_rfc3339, "2026-03-10T15:42:00Z");
+    }
+
+    #[test]
+    fn day_windows_split_history_on_day_boundaries() {
+        let mode = WindowMode::Custom {
+            utc_offset_minutes: -300,
+            day_start_hour: 0,
+        };
+
+        let windows = day_windows_at(&mode, 3, at("2026-03-10T15:42:31Z"));
+        let dates: Vec<_> = windows.iter().map(|window| window.date.as_str()).collect();
+        assert_eq!(dates, ["2026-03-08", "2026-03-09", "2026-03-10"]);
+        assert_eq!(windows[0].start_rfc3339, "2026-03-08T05:00:00Z");
+        assert_eq!(windows[0].end_rfc3339, "2026-03-09T05:00:00Z");
+        assert_eq!(windows[2].end_rfc3339, "2026-03-10T15:42:00Z");
+    }
+
+    #[test]
+    fn custom_window_starts_at_day_start_hour_in_offset() {
+        let mode = WindowMode::Custom {
+            utc_offset_minutes: 330,
+            day_start_hour: 4,
+        };
+
+        let window = tracking_window_at(&mode, at("2026-03-10T15:42:00Z"));
+        assert_eq!(window.start_rfc3339, "2026-03-09T22:30:00Z");
+
+        let before_start = tracking_window_at(&mode, at("2026-03-09T22:00:00Z"));
+        assert_eq!(before_start.start_rfc3339, "2026-03-08T22:30:00Z");
+    }
+
+    #[test]
+    fn invalid_custom_window_falls_back_to_rolling() {
+        let mode = WindowMode::Custom {
+            utc_offset_minutes: 0,
+            day_start_hour: 30,
+        };
+        let window = tracking_window_at(&mode, at("2026-03-10T15:42:00Z"));
+        assert_eq!(window.start_rfc3339, "2026-03-09T15:42:00Z");
+    }
+}
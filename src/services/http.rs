@@ -0,0 +1,164 @@
+use super::ratelimit::{EndpointStatus, RateLimiter};
+use crate::error::FlavortimeError;
+use reqwest::{Client, Method, Proxy, RequestBuilder, Response};
+use std::sync::Arc;
+use std::time::Duration;
+
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(15);
+pub const HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(10);
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_RETRIES: u32 = 2;
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+
+#[derive(Clone)]
+pub struct HttpClient {
+    client: Client,
+    limiter: Arc<RateLimiter>,
+}
+
+impl HttpClient {
+    pub fn new(proxy: Option<&str>) -> Self {
+        let mut builder = Client::builder()
+            .user_agent(user_agent())
+            .connect_timeout(CONNECT_TIMEOUT)
+            .timeout(DEFAULT_TIMEOUT)
+            .pool_idle_timeout(Duration::from_secs(90));
+
+        if let Some(proxy_url) = proxy.map(str::trim).filter(|value| !value.is_empty()) {
+            match Proxy::all(proxy_url) {
+                Ok(proxy) => builder = builder.proxy(proxy),
+                Err(err) => log::warn!("Ignoring invalid HTTP proxy `{proxy_url}`: {err}"),
+            }
+        }
+
+        let client = builder.build().unwrap_or_else(|err| {
+            log::error!("Failed to build HTTP client, using defaults: {err}");
+            Client::new()
+        });
+        Self {
+            client,
+            limiter: Arc::new(RateLimiter::default()),
+        }
+    }
+
+    pub fn get(&self, url: impl AsRef<str>) -> RequestBuilder {
+        self.client.get(url.as_ref())
+    }
+
+    pub fn post(&self, url: impl AsRef<str>) -> RequestBuilder {
+        self.client.post(url.as_ref())
+    }
+
+    pub fn rate_limits(&self) -> Vec<EndpointStatus> {
+        self.limiter.status()
+    }
+
+    pub async fn send(
+        &self,
+        request: RequestBuilder,
+        timeout: Duration,
+    ) -> Result<Response, FlavortimeError> {
+        let endpoint = endpoint_key(&request);
+        if let Some(endpoint) = &endpoint {
+            self.limiter.check(endpoint)?;
+        }
+
+        let response = self.send_with_retries(request, timeout).await?;
+        if let Some(endpoint) = &endpoint {
+            self.limiter
+                .record(endpoint, response.status(), response.headers());
+        }
+        Ok(response)
+    }
+
+    async fn send_with_retries(
+        &self,
+        request: RequestBuilder,
+        timeout: Duration,
+    ) -> Result<Response, reqwest::Error> {
+        let retries = if is_idempotent(&request) {
+            MAX_RETRIES
+        } else {
+            0
+        };
+        let mut attempt = 0;
+        loop {
+            let Some(next) = request.try_clone() else {
+                return request.timeout(timeout).send().await;
+            };
+
+            match next.timeout(timeout).send().await {
+                Ok(response) if response.status().is_server_error() && attempt < retries => {
+                    log::debug!(
+                        "Retrying {} after server error {}",
+                        response.url(),
+                        response.status()
+                    );
+                }
+                Err(err) if is_retryable(&err) && attempt < retries => {
+                    log::debug!("Retrying request after transport error: {err}");
+                }
+                result => return result,
+            }
+
+            tokio::time::sleep(RETRY_BASE_DELAY * 2u32.pow(attempt)).await;
+            attempt += 1;
+        }
+    }
+}
+
+pub fn user_agent() -> String {
+    format!(
+        "Flavortime/{} ({})",
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS
+    )
+}
+
+fn endpoint_key(request: &RequestBuilder) -> Option<String> {
+    let request = request.try_clone()?.build().ok()?;
+    let mut url = request.url().clone();
+    url.set_query(None);
+    url.set_fragment(None);
+    Some(format!("{} {url}", request.method()))
+}
+
+fn is_idempotent(request: &RequestBuilder) -> bool {
+    request
+        .try_clone()
+        .and_then(|request| request.build().ok())
+        .is_some_and(|request| {
+            matches!(
+                *request.method(),
+                Method::GET | Method::HEAD | Method::PUT | Method::DELETE | Method::OPTIONS
+            )
+        })
+}
+
+fn is_retryable(err: &reqwest::Error) -> bool {
+    err.is_connect() || err.is_timeout()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::mock::test_server;
+    use serde_json::Value;
+    use tauri::async_runtime::block_on;
+
+    #[test]
+    fn server_errors_only_retry_idempotent_requests() {
+        let client = HttpClient::new(None);
+        let attempts = |request| {
+            let response = block_on(client.send(request, Duration::from_secs(5))).expect("send");
+            assert_eq!(response.status().as_u16(), 503);
+            let body: Value = block_on(response.json()).expect("json");
+            body["attempts"].as_u64()
+        };
+
+        let url = |key: &str| format!("{}/api/v1/failing/{key}", test_server().base_url());
+        assert_eq!(attempts(client.get(url("get"))), Some(3));
+        assert_eq!(attempts(client.post(url("post"))), Some(1));
+    }
+}
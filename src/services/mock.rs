@@ -0,0 +1,405 @@
+use chrono::{Duration as ChronoDuration, NaiveDate, SecondsFormat, Utc};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+pub const MOCK_UNAUTHORIZED: &str = "mock-unauthorized";
+pub const MOCK_RATE_LIMITED: &str = "mock-rate-limited";
+pub const MOCK_SLOW: &str = "mock-slow";
+pub const MOCK_UNKNOWN_USER: &str = "mock-unknown";
+pub const MOCK_SLACK_ID: &str = "U0MOCKUSER";
+pub const MOCK_WAKATIME_KEY: &str = "mock-wakatime-key";
+pub const MOCK_STATUS_BAR_KEY: &str = "mock-status-bar";
+pub const SESSION_HEARTBEATS: u32 = 3;
+
+pub const RETRY_AFTER_SECS: u64 = 30;
+const DEFAULT_SLOW_DELAY: Duration = Duration::from_secs(3);
+// Basic auth headers for the WakaTime keys above. The status bar key stands in
+// for servers that only implement `status_bar/today`.
+const WAKATIME_AUTH: [(&str, &str); 2] = [
+    (MOCK_WAKATIME_KEY, "Basic bW9jay13YWthdGltZS1rZXk6"),
+    (MOCK_STATUS_BAR_KEY, "Basic bW9jay1zdGF0dXMtYmFyOg=="),
+];
+
+struct Request {
+    method: String,
+    path: String,
+    query: HashMap<String, String>,
+    headers: HashMap<String, String>,
+    body: Value,
+}
+
+struct Response {
+    status: u16,
+    headers: Vec<(&'static str, String)>,
+    body: Value,
+}
+
+impl Response {
+    fn json(status: u16, body: Value) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body,
+        }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Self::json(status, json!({ "error": message }))
+    }
+}
+
+#[derive(Default)]
+struct MockState {
+    sessions: HashMap<String, u32>,
+    next_session: u64,
+    #[cfg(test)]
+    failures: HashMap<String, u32>,
+}
+
+pub struct MockServer {
+    address: SocketAddr,
+}
+
+impl MockServer {
+    pub fn start(slow_delay: Option<Duration>) -> std::io::Result<Self> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+        let address = listener.local_addr()?;
+        let state = Arc::new(Mutex::new(MockState::default()));
+        let slow_delay = slow_delay.unwrap_or(DEFAULT_SLOW_DELAY);
+
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let state = Arc::clone(&state);
+                thread::spawn(move || {
+                    if let Err(err) = serve(stream, &state, slow_delay) {
+                        log::warn!("Mock server request failed: {err}");
+                    }
+                });
+            }
+        });
+
+        Ok(Self { address })
+    }
+
+    pub fn base_url(&self) -> String {
+        format!("http://{}", self.address)
+    }
+
+    #[cfg(feature = "mock")]
+    pub fn install_env(&self) {
+        let base_url = self.base_url();
+        for name in [
+            "FLAVORTIME_HACKATIME_BASE_URL",
+            "FLAVORTIME_PYRAMID_BASE_URL",
+            "FLAVORTIME_FLAVORTOWN_BASE_URL",
+        ] {
+            std::env::set_var(name, &base_url);
+        }
+    }
+}
+
+#[cfg(test)]
+pub const TEST_SLOW_DELAY: Duration = Duration::from_millis(200);
+
+#[cfg(test)]
+pub fn test_server() -> &'static MockServer {
+    static SERVER: std::sync::OnceLock<MockServer> = std::sync::OnceLock::new();
+    SERVER.get_or_init(|| MockServer::start(Some(TEST_SLOW_DELAY)).expect("start mock server"))
+}
+
+fn serve(stream: TcpStream, state: &Mutex<MockState>, slow_delay: Duration) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let request = read_request(&mut reader)?;
+    let response = route(&request, state, slow_delay);
+    write_response(stream, &response)
+}
+
+fn read_request(reader: &mut impl BufRead) -> std::io::Result<Request> {
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let mut parts = line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let target = parts.next().unwrap_or_default().to_string();
+
+    let mut headers = HashMap::new();
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+        }
+    }
+
+    let length = headers
+        .get("content-length")
+        .and_then(|value| value.parse::<usize>().ok())
+        .unwrap_or(0);
+    let mut raw_body = vec![0; length];
+    reader.read_exact(&mut raw_body)?;
+
+    let (path, query) = target.split_once('?').unwrap_or((target.as_str(), ""));
+    let query = query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(key, value)| {
+            let value = urlencoding::decode(value)
+                .map(|value| value.into_owned())
+                .unwrap_or_else(|_| value.to_string());
+            (key.to_string(), value)
+        })
+        .collect();
+
+    Ok(Request {
+        method,
+        path: path.to_string(),
+        query,
+        headers,
+        body: serde_json::from_slice(&raw_body).unwrap_or(Value::Null),
+    })
+}
+
+fn write_response(mut stream: TcpStream, response: &Response) -> std::io::Result<()> {
+    let body = response.body.to_string();
+    let mut head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n",
+        response.status,
+        reason(response.status),
+        body.len()
+    );
+    for (name, value) in &response.headers {
+        head.push_str(&format!("{name}: {value}\r\n"));
+    }
+    head.push_str("\r\n");
+    stream.write_all(head.as_bytes())?;
+    stream.write_all(body.as_bytes())?;
+    stream.flush()
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        429 => "Too Many Requests",
+        503 => "Service Unavailable",
+        _ => "Unknown",
+    }
+}
+
+fn route(request: &Request, state: &Mutex<MockState>, slow_delay: Duration) -> Response {
+    let identity = identity(request);
+    match identity.as_deref() {
+        Some(MOCK_UNAUTHORIZED) => return Response::error(401, "Unauthorized"),
+        Some(MOCK_RATE_LIMITED) => {
+            let mut response = Response::error(429, "Too many requests");
+            response
+                .headers
+                .push(("Retry-After", RETRY_AFTER_SECS.to_string()));
+            return response;
+        }
+        Some(MOCK_SLOW) => thread::sleep(slow_delay),
+        _ => {}
+    }
+
+    let segments: Vec<&str> = request.path.trim_matches('/').split('/').collect();
+    match (request.method.as_str(), segments.as_slice()) {
+        ("GET", ["api", "v1", "users", "me"]) => {
+            let slack_id = identity
+                .filter(|value| value.starts_with("mock-"))
+                .unwrap_or_else(|| MOCK_SLACK_ID.to_string());
+            Response::json(200, json!({ "slack_id": slack_id }))
+        }
+        ("POST", ["api", "v1", "flavortime", "session"]) => {
+            let mut state = state.lock().unwrap_or_else(|err| err.into_inner());
+            state.next_session += 1;
+            let session_id = format!("mock-session-{}", state.next_session);
+            state.sessions.insert(session_id.clone(), 0);
+            Response::json(200, json!({ "session_id": session_id }))
+        }
+        ("POST", ["api", "v1", "flavortime", "heartbeat"]) => {
+            let mut state = state.lock().unwrap_or_else(|err| err.into_inner());
+            let session_id = session_id(request);
+            match state.sessions.get_mut(&session_id) {
+                Some(count) if *count < SESSION_HEARTBEATS => {
+                    *count += 1;
+                    let active_users = state.sessions.len() as u64;
+                    Response::json(200, json!({ "active_users": active_users }))
+                }
+                Some(_) => {
+                    state.sessions.remove(&session_id);
+                    Response::error(404, "Session expired")
+                }
+                None => Response::error(404, "Session not found"),
+            }
+        }
+        ("POST", ["api", "v1", "flavortime", "close"]) => {
+            let mut state = state.lock().unwrap_or_else(|err| err.into_inner());
+            if state.sessions.remove(&session_id(request)).is_none() {
+                return Response::error(404, "Session not found");
+            }
+            Response::json(200, json!({ "active_users": state.sessions.len() }))
+        }
+        ("GET", ["api", "v1", "users", _, "projects", "details"]) => {
+            Response::json(200, projects_fixture())
+        }
+        ("GET", ["api", "v1", "users", "current", endpoint @ ..]) => {
+            let Some(key) = wakatime_key(request) else {
+                return Response::error(401, "Unauthorized");
+            };
+            match (key, endpoint) {
+                (_, ["status_bar", "today"]) => {
+                    Response::json(200, json!({ "data": day_summary_fixture() }))
+                }
+                (MOCK_STATUS_BAR_KEY, _) => Response::error(404, "Not found"),
+                (_, ["summaries"]) => {
+                    match (query_date(request, "start"), query_date(request, "end")) {
+                        (Some(start), Some(end)) if utc(request) => {
+                            let days = start.iter_days().take_while(|day| *day <= end);
+                            let data: Vec<Value> = days.map(|_| day_summary_fixture()).collect();
+                            Response::json(200, json!({ "data": data }))
+                        }
+                        _ => Response::error(400, "Invalid range"),
+                    }
+                }
+                (_, ["durations"]) => match query_date(request, "date") {
+                    Some(date) if utc(request) => Response::json(200, durations_fixture(date)),
+                    _ => Response::error(400, "Invalid date"),
+                },
+                _ => Response::error(404, "Not found"),
+            }
+        }
+        ("GET", ["api", "v1", "codes", "lookup"]) => {
+            if request.query.get("slack_id").map(String::as_str) == Some(MOCK_UNKNOWN_USER) {
+                return Response::json(200, json!({ "error": "User not found" }));
+            }
+            Response::json(200, codes_fixture())
+        }
+        #[cfg(test)]
+        (_, ["api", "v1", "failing", key]) => {
+            let mut state = state.lock().unwrap_or_else(|err| err.into_inner());
+            let attempts = state.failures.entry(key.to_string()).or_default();
+            *attempts += 1;
+            Response::json(503, json!({ "attempts": *attempts }))
+        }
+        _ => Response::error(404, "Not found"),
+    }
+}
+
+fn identity(request: &Request) -> Option<String> {
+    request
+        .headers
+        .get("authorization")
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(|value| value.trim().to_string())
+        .or_else(|| request.query.get("slack_id").cloned())
+        .or_else(|| {
+            let segments: Vec<&str> = request.path.trim_matches('/').split('/').collect();
+            match segments.as_slice() {
+                ["api", "v1", "users", username, "projects", "details"] => {
+                    urlencoding::decode(username)
+                        .ok()
+                        .map(|value| value.into_owned())
+                }
+                _ => None,
+            }
+        })
+}
+
+fn wakatime_key(request: &Request) -> Option<&'static str> {
+    let header = request.headers.get("authorization")?;
+    WAKATIME_AUTH
+        .iter()
+        .find(|(_, auth)| auth == header)
+        .map(|(key, _)| *key)
+}
+
+fn query_date(request: &Request, name: &str) -> Option<NaiveDate> {
+    let value = request.query.get(name)?;
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()
+}
+
+fn utc(request: &Request) -> bool {
+    request.query.get("timezone").map(String::as_str) == Some("UTC")
+}
+
+fn session_id(request: &Request) -> String {
+    request
+        .headers
+        .get("x-flavortime-session-id")
+        .cloned()
+        .or_else(|| {
+            request
+                .body
+                .get("session_id")
+                .and_then(Value::as_str)
+                .map(ToOwned::to_owned)
+        })
+        .unwrap_or_default()
+}
+
+fn projects_fixture() -> Value {
+    let now = Utc::now();
+    let at = |minutes: i64| {
+        (now - ChronoDuration::minutes(minutes)).to_rfc3339_opts(SecondsFormat::Secs, true)
+    };
+    json!({
+        "projects": [
+            {
+                "name": "flavortime",
+                "total_seconds": 9_240.0,
+                "most_recent_heartbeat": at(2),
+                "last_heartbeat": at(2)
+            },
+            {
+                "name": "pyramid-scheme",
+                "total_seconds": 3_120.0,
+                "most_recent_heartbeat": at(95),
+                "last_heartbeat": at(95)
+            },
+            {
+                "name": "dotfiles",
+                "total_seconds": 540.0,
+                "last_heartbeat": at(600)
+            }
+        ]
+    })
+}
+
+// Day totals that match the durations below.
+fn day_summary_fixture() -> Value {
+    json!({
+        "projects": [
+            { "name": "flavortime", "total_seconds": 3_600.0 },
+            { "name": "dotfiles", "total_seconds": 1_800.0 }
+        ]
+    })
+}
+
+fn durations_fixture(date: NaiveDate) -> Value {
+    let midnight = date
+        .and_hms_opt(0, 0, 0)
+        .map_or(0, |at| at.and_utc().timestamp());
+    json!({
+        "data": [
+            { "project": "flavortime", "time": midnight + 10 * 3600, "duration": 3_600.0 },
+            { "project": "dotfiles", "time": midnight + 23 * 3600, "duration": 1_800.0 }
+        ]
+    })
+}
+
+fn codes_fixture() -> Value {
+    json!({
+        "codes": [
+            { "code": "MOCKCUSTOM", "type": "custom" },
+            { "code": "MOCK1234", "type": "generated" }
+        ]
+    })
+}
@@ -1,4 +1,12 @@
+pub mod api;
 pub mod discord;
 pub mod flavortown;
 pub mod hackatime;
+pub mod http;
+#[cfg(any(test, feature = "mock"))]
+pub mod mock;
+pub mod presence;
+pub mod privacy;
 pub mod pyramid;
+pub mod ratelimit;
+pub mod wakatime;
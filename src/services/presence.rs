@@ -0,0 +1,350 @@
+use crate::data::config::PresenceTemplate;
+use crate::data::locale::rich_presence_text;
+use crate::error::FlavortimeError;
+use serde::Serialize;
+
+pub const FIELD_LIMIT: usize = 128;
+pub const BUTTON_LABEL_LIMIT: usize = 32;
+pub const PLACEHOLDERS: &[&str] = &[
+    "project",
+    "project_hours",
+    "today",
+    "session_length",
+    "referral_code",
+    "language",
+    "window",
+    "streak",
+    "goal",
+];
+
+#[derive(Clone, Default)]
+pub struct PresenceValues {
+    pub project: Option<String>,
+    pub project_hours: Option<f64>,
+    pub today: Option<f64>,
+    pub session_length: Option<u64>,
+    pub referral_code: Option<String>,
+    pub language: Option<String>,
+    pub rolling_window: bool,
+    pub streak: Option<u32>,
+    pub daily_goal: Option<f64>,
+}
+
+#[derive(Clone, Default, Serialize)]
+pub struct RenderedPresence {
+    pub details: Option<String>,
+    pub state: Option<String>,
+    pub large_text: Option<String>,
+    pub small_text: Option<String>,
+    pub button_label: Option<String>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct PresencePreview {
+    #[serde(flatten)]
+    pub presence: RenderedPresence,
+    pub button_url: Option<String>,
+}
+
+enum Segment<'a> {
+    Text(&'a str),
+    Placeholder(&'a str),
+    Optional(Vec<Segment<'a>>),
+}
+
+impl RenderedPresence {
+    pub fn is_empty(&self) -> bool {
+        self.details.is_none() && self.state.is_none() && self.small_text.is_none()
+    }
+}
+
+impl PresenceValues {
+    pub fn sample(rolling_window: bool) -> Self {
+        Self {
+            project: Some("flavortime-desktop-app".to_string()),
+            project_hours: Some(12.75),
+            today: Some(23.5),
+            session_length: Some(5 * 3600 + 42 * 60),
+            referral_code: Some("FLAVOR1234".to_string()),
+            language: Some("TypeScript".to_string()),
+            rolling_window,
+            streak: Some(128),
+            daily_goal: Some(12.5),
+        }
+    }
+
+    fn get(&self, name: &str) -> Option<String> {
+        match name {
+            "project" => non_empty(self.project.as_deref()),
+            "project_hours" => self
+                .project_hours
+                .filter(|hours| *hours > 0.0)
+                .map(fmt_hours),
+            "today" => self.today.filter(|hours| *hours > 0.0).map(fmt_hours),
+            "session_length" => self
+                .session_length
+                .filter(|seconds| *seconds >= 60)
+                .map(|seconds| fmt_hours(seconds as f64 / 3600.0)),
+            "referral_code" => non_empty(self.referral_code.as_deref()),
+            "language" => non_empty(self.language.as_deref()),
+            "window" => Some(window_label(self.rolling_window)),
+            "streak" => self
+                .streak
+                .filter(|days| *days > 0)
+                .map(|days| days.to_string()),
+            "goal" => self.daily_goal.filter(|hours| *hours > 0.0).map(fmt_hours),
+            _ => None,
+        }
+    }
+}
+
+pub fn render(template: &PresenceTemplate, values: &PresenceValues) -> RenderedPresence {
+    let state = if values.project.is_some() {
+        &template.state
+    } else {
+        &template.state_idle
+    };
+
+    RenderedPresence {
+        details: render_field(&template.details, values, FIELD_LIMIT),
+        state: render_field(state, values, FIELD_LIMIT),
+        large_text: render_field(&template.large_text, values, FIELD_LIMIT),
+        small_text: render_field(&template.small_text, values, FIELD_LIMIT),
+        button_label: render_field(&template.button_label, values, BUTTON_LABEL_LIMIT),
+    }
+}
+
+pub fn validate(template: &PresenceTemplate) -> Result<(), FlavortimeError> {
+    let fields = [
+        ("details", &template.details, FIELD_LIMIT),
+        ("state", &template.state, FIELD_LIMIT),
+        ("state_idle", &template.state_idle, FIELD_LIMIT),
+        ("large_text", &template.large_text, FIELD_LIMIT),
+        ("small_text", &template.small_text, FIELD_LIMIT),
+        ("button_label", &template.button_label, BUTTON_LABEL_LIMIT),
+    ];
+
+    for (name, source, limit) in fields {
+        let segments =
+            parse(source).map_err(|err| FlavortimeError::InvalidInput(format!("{name}: {err}")))?;
+        for rolling_window in [false, true] {
+            let rendered = render_segments(&segments, &PresenceValues::sample(rolling_window));
+            let length = rendered.trim().chars().count();
+            if length > limit {
+                return Err(FlavortimeError::InvalidInput(format!(
+                    "{name} renders to {length} characters, Discord allows at most {limit}"
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn referral_url(referral_code: Option<&str>) -> String {
+    let text = rich_presence_text();
+    let host = non_empty(Some(text.referral_host.as_str()))
+        .unwrap_or_else(|| "flavortown.hackclub.com".to_string());
+    match non_empty(referral_code) {
+        Some(code) => format!("https://{host}/{code}"),
+        None => format!("https://{host}"),
+    }
+}
+
+pub fn fmt_hours(hours: f64) -> String {
+    let minutes = (hours * 60.0).round().max(0.0) as u32;
+    let (whole, rem) = (minutes / 60, minutes % 60);
+    if whole > 0 {
+        format!("{whole}h {rem}m")
+    } else {
+        format!("{rem}m")
+    }
+}
+
+fn render_field(source: &str, values: &PresenceValues, limit: usize) -> Option<String> {
+    let segments = match parse(source) {
+        Ok(segments) => segments,
+        Err(err) => {
+            log::warn!("Ignoring invalid presence template `{source}`: {err}");
+            return None;
+        }
+    };
+    let rendered = render_segments(&segments, values);
+    let rendered = rendered.trim();
+    if rendered.is_empty() {
+        return None;
+    }
+    Some(truncate(rendered, limit))
+}
+
+fn render_segments(segments: &[Segment<'_>], values: &PresenceValues) -> String {
+    let mut output = String::new();
+    for segment in segments {
+        match segment {
+            Segment::Text(text) => output.push_str(text),
+            Segment::Placeholder(name) => {
+                if let Some(value) = values.get(name) {
+                    output.push_str(&value);
+                }
+            }
+            Segment::Optional(inner) => {
+                let complete = inner.iter().all(|segment| match segment {
+                    Segment::Placeholder(name) => values.get(name).is_some(),
+                    _ => true,
+                });
+                if complete {
+                    output.push_str(&render_segments(inner, values));
+                }
+            }
+        }
+    }
+    output
+}
+
+fn parse(source: &str) -> Result<Vec<Segment<'_>>, String> {
+    let mut root = Vec::new();
+    let mut optional: Option<Vec<Segment<'_>>> = None;
+    let mut rest = source;
+
+    while !rest.is_empty() {
+        let next = rest.find(['{', '[', ']']).unwrap_or(rest.len());
+        if next > 0 {
+            let target = optional.as_mut().unwrap_or(&mut root);
+            target.push(Segment::Text(&rest[..next]));
+            rest = &rest[next..];
+            continue;
+        }
+
+        match rest.as_bytes()[0] {
+            b'{' => {
+                let end = rest.find('}').ok_or_else(|| "unclosed `{`".to_string())?;
+                let name = &rest[1..end];
+                if !PLACEHOLDERS.contains(&name) {
+                    return Err(format!("unknown placeholder `{{{name}}}`"));
+                }
+                let target = optional.as_mut().unwrap_or(&mut root);
+                target.push(Segment::Placeholder(name));
+                rest = &rest[end + 1..];
+            }
+            b'[' => {
+                if optional.is_some() {
+                    return Err("optional sections cannot be nested".to_string());
+                }
+                optional = Some(Vec::new());
+                rest = &rest[1..];
+            }
+            _ => {
+                let inner = optional
+                    .take()
+                    .ok_or_else(|| "unexpected `]`".to_string())?;
+                root.push(Segment::Optional(inner));
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    if optional.is_some() {
+        return Err("unclosed `[`".to_string());
+    }
+    Ok(root)
+}
+
+fn truncate(value: &str, limit: usize) -> String {
+    if value.chars().count() <= limit {
+        return value.to_string();
+    }
+    let mut truncated = value.chars().take(limit - 1).collect::<String>();
+    truncated.push('…');
+    truncated
+}
+
+fn window_label(rolling_window: bool) -> String {
+    let text = rich_presence_text();
+    let prefix = if rolling_window {
+        &text.time_rolling_prefix
+    } else {
+        &text.time_today_prefix
+    };
+    prefix.trim().trim_end_matches(':').to_string()
+}
+
+fn non_empty(value: Option<&str>) -> Option<String> {
+    value
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn template(state: &str) -> PresenceTemplate {
+        PresenceTemplate {
+            state: state.to_string(),
+            ..PresenceTemplate::default()
+        }
+    }
+
+    #[test]
+    fn renders_placeholders_and_drops_incomplete_sections() {
+        let values = PresenceValues {
+            project: Some("flavortime".to_string()),
+            project_hours: Some(1.5),
+            today: Some(3.0),
+            ..PresenceValues::default()
+        };
+        let rendered = render(
+            &template("{project}[ ({project_hours})][ in {language}] · {today}"),
+            &values,
+        );
+        assert_eq!(
+            rendered.state.as_deref(),
+            Some("flavortime (1h 30m) · 3h 0m")
+        );
+    }
+
+    #[test]
+    fn default_template_matches_previous_presence() {
+        let values = PresenceValues {
+            project: Some("flavortime".to_string()),
+            project_hours: Some(0.5),
+            today: Some(2.0),
+            ..PresenceValues::default()
+        };
+        let rendered = render(&PresenceTemplate::default(), &values);
+        assert_eq!(
+            rendered.state.as_deref(),
+            Some("Project: flavortime (30m spent)")
+        );
+        assert_eq!(rendered.small_text.as_deref(), Some("Today: 2h 0m logged"));
+
+        let idle = render(&PresenceTemplate::default(), &PresenceValues::default());
+        assert_eq!(idle.state.as_deref(), Some("Flavortown"));
+        assert_eq!(idle.small_text, None);
+    }
+
+    #[test]
+    fn validation_rejects_bad_syntax_and_long_fields() {
+        assert!(validate(&PresenceTemplate::default()).is_ok());
+        assert!(validate(&template("{projcet}")).is_err());
+        assert!(validate(&template("[{project}")).is_err());
+        assert!(validate(&template("[[{project}]]")).is_err());
+        assert!(validate(&template(&"x".repeat(FIELD_LIMIT + 1))).is_err());
+        assert!(validate(&PresenceTemplate {
+            button_label: "Join me on Flavortown with {referral_code} today".to_string(),
+            ..PresenceTemplate::default()
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn rendered_fields_are_truncated_to_discord_limits() {
+        let values = PresenceValues {
+            project: Some("p".repeat(200)),
+            ..PresenceValues::default()
+        };
+        let state = render(&template("{project}"), &values).state.unwrap();
+        assert_eq!(state.chars().count(), FIELD_LIMIT);
+        assert!(state.ends_with('…'));
+    }
+}
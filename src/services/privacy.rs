@@ -0,0 +1,142 @@
+use crate::data::config::{PrivacyAction, PrivacyMatch, PrivacyRule};
+use crate::data::locale::rich_presence_text;
+use crate::error::FlavortimeError;
+use regex::{Regex, RegexBuilder};
+
+pub struct PrivacyRules {
+    rules: Vec<(Regex, PrivacyAction)>,
+}
+
+impl PrivacyRules {
+    pub fn compile(rules: &[PrivacyRule]) -> Result<Self, FlavortimeError> {
+        let rules = rules
+            .iter()
+            .map(|rule| {
+                if let PrivacyAction::Alias { alias } = &rule.action {
+                    if alias.trim().is_empty() {
+                        return Err(FlavortimeError::InvalidInput(format!(
+                            "Alias for {} is empty",
+                            rule.pattern
+                        )));
+                    }
+                }
+                Ok((matcher(rule)?, rule.action.clone()))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { rules })
+    }
+
+    pub fn redact_all() -> Self {
+        Self {
+            rules: vec![(
+                Regex::new(".*").expect("static regex"),
+                PrivacyAction::Secret,
+            )],
+        }
+    }
+
+    pub fn public_name(&self, name: &str) -> Option<String> {
+        let Some((_, action)) = self.rules.iter().find(|(regex, _)| regex.is_match(name)) else {
+            return Some(name.to_string());
+        };
+        match action {
+            PrivacyAction::Hide => None,
+            PrivacyAction::Alias { alias } => Some(alias.trim().to_string()),
+            PrivacyAction::Secret => Some(rich_presence_text().secret_project.clone()),
+        }
+    }
+}
+
+fn matcher(rule: &PrivacyRule) -> Result<Regex, FlavortimeError> {
+    let pattern = rule.pattern.trim();
+    if pattern.is_empty() {
+        return Err(FlavortimeError::InvalidInput(
+            "Privacy rule pattern is required".to_string(),
+        ));
+    }
+
+    let source = match rule.matcher {
+        PrivacyMatch::Exact => format!("^{}$", regex::escape(pattern)),
+        PrivacyMatch::Glob => glob_to_regex(pattern),
+        PrivacyMatch::Regex => pattern.to_string(),
+    };
+    RegexBuilder::new(&source)
+        .case_insensitive(rule.matcher != PrivacyMatch::Regex)
+        .build()
+        .map_err(|err| FlavortimeError::InvalidInput(format!("Invalid pattern {pattern}: {err}")))
+}
+
+fn glob_to_regex(pattern: &str) -> String {
+    let mut source = String::from("^");
+    for ch in pattern.chars() {
+        match ch {
+            '*' => source.push_str(".*"),
+            '?' => source.push('.'),
+            _ => source.push_str(&regex::escape(ch.encode_utf8(&mut [0; 4]))),
+        }
+    }
+    source.push('$');
+    source
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(pattern: &str, matcher: PrivacyMatch, action: PrivacyAction) -> PrivacyRule {
+        PrivacyRule {
+            pattern: pattern.to_string(),
+            matcher,
+            action,
+        }
+    }
+
+    #[test]
+    fn first_matching_rule_decides_public_name() {
+        let rules = PrivacyRules::compile(&[
+            rule("acme-payroll", PrivacyMatch::Exact, PrivacyAction::Hide),
+            rule(
+                "acme-*",
+                PrivacyMatch::Glob,
+                PrivacyAction::Alias {
+                    alias: "Client work".to_string(),
+                },
+            ),
+            rule(r"^secret-\d+$", PrivacyMatch::Regex, PrivacyAction::Secret),
+        ])
+        .expect("compile rules");
+
+        assert_eq!(rules.public_name("ACME-Payroll"), None);
+        assert_eq!(
+            rules.public_name("acme-website").as_deref(),
+            Some("Client work")
+        );
+        assert_eq!(
+            rules.public_name("secret-42"),
+            Some(rich_presence_text().secret_project.clone())
+        );
+        assert_eq!(rules.public_name("Secret-42").as_deref(), Some("Secret-42"));
+        assert_eq!(
+            rules.public_name("acme.website").as_deref(),
+            Some("acme.website")
+        );
+    }
+
+    #[test]
+    fn invalid_rules_are_rejected() {
+        let bad_regex = rule("(", PrivacyMatch::Regex, PrivacyAction::Hide);
+        assert!(PrivacyRules::compile(&[bad_regex]).is_err());
+
+        let empty_alias = rule(
+            "flavortime",
+            PrivacyMatch::Exact,
+            PrivacyAction::Alias {
+                alias: " ".to_string(),
+            },
+        );
+        assert!(PrivacyRules::compile(&[empty_alias]).is_err());
+
+        let blank = rule(" ", PrivacyMatch::Glob, PrivacyAction::Secret);
+        assert!(PrivacyRules::compile(&[blank]).is_err());
+    }
+}
@@ -1,4 +1,6 @@
-use crate::data::{config::Referral, runtime::runtime};
+use super::http::{HttpClient, DEFAULT_TIMEOUT};
+use crate::data::config::Referral;
+use crate::error::FlavortimeError;
 use serde::Deserialize;
 
 #[derive(Deserialize)]
@@ -15,35 +17,34 @@ struct LookupResponse {
     codes: Vec<ReferralCode>,
 }
 
-pub async fn fetch_codes(slack_id: &str) -> Result<Vec<Referral>, String> {
-    let rt = runtime();
+pub async fn fetch_codes(
+    http: &HttpClient,
+    base_url: &str,
+    campaign_slug: &str,
+    slack_id: &str,
+) -> Result<Vec<Referral>, FlavortimeError> {
     let url = format!(
-        "{}/api/v1/codes/lookup?slack_id={}&campaign_slug={}",
-        rt.pyramid_base_url,
+        "{base_url}/api/v1/codes/lookup?slack_id={}&campaign_slug={campaign_slug}",
         urlencoding::encode(slack_id),
-        rt.flavortown_campaign_slug
     );
 
-    let res = reqwest::Client::new()
-        .get(url)
-        .send()
-        .await
-        .map_err(|err| err.to_string())?;
+    let res = http.send(http.get(url), DEFAULT_TIMEOUT).await?;
 
     if !res.status().is_success() {
-        return Err(format!("Referral lookup failed: {}", res.status()));
+        return Err(FlavortimeError::from_response(res, "Referral lookup failed").await);
     }
 
-    let body = res
-        .json::<LookupResponse>()
-        .await
-        .map_err(|err| err.to_string())?;
+    let status = res.status().as_u16();
+    let body = res.json::<LookupResponse>().await?;
 
     if let Some(error) = body.error.as_deref() {
         return if error == "User not found" {
             Ok(Vec::new())
         } else {
-            Err(error.to_string())
+            Err(FlavortimeError::Http {
+                status,
+                message: error.to_string(),
+            })
         };
     }
 
@@ -0,0 +1,293 @@
+use crate::error::FlavortimeError;
+use chrono::DateTime;
+use reqwest::{
+    header::{HeaderMap, RETRY_AFTER},
+    StatusCode,
+};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::OnceCell;
+
+const BASE_BACKOFF_SECS: u64 = 30;
+const MAX_BACKOFF_SECS: u64 = 15 * 60;
+const EPOCH_RESET_THRESHOLD: u64 = 1_000_000_000;
+
+#[derive(Default)]
+struct Endpoint {
+    blocked_until: Option<u64>,
+    consecutive_limited: u32,
+    limit: Option<u64>,
+    remaining: Option<u64>,
+    reset_at: Option<u64>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct EndpointStatus {
+    pub endpoint: String,
+    pub retry_after: Option<u64>,
+    pub consecutive_limited: u32,
+    pub limit: Option<u64>,
+    pub remaining: Option<u64>,
+    pub reset_at: Option<u64>,
+}
+
+#[derive(Default)]
+pub struct RateLimiter {
+    endpoints: Mutex<HashMap<String, Endpoint>>,
+}
+
+impl RateLimiter {
+    pub fn check(&self, endpoint: &str) -> Result<(), FlavortimeError> {
+        self.check_at(endpoint, unix_now())
+    }
+
+    pub fn record(&self, endpoint: &str, status: StatusCode, headers: &HeaderMap) {
+        self.record_at(endpoint, status, headers, unix_now());
+    }
+
+    pub fn status(&self) -> Vec<EndpointStatus> {
+        self.status_at(unix_now())
+    }
+
+    fn check_at(&self, endpoint: &str, now: u64) -> Result<(), FlavortimeError> {
+        let retry_after = self
+            .lock()
+            .get(endpoint)
+            .and_then(|entry| entry.blocked_until)
+            .filter(|until| *until > now)
+            .map(|until| until - now);
+        match retry_after {
+            Some(retry_after) => Err(FlavortimeError::RateLimited {
+                retry_after: Some(retry_after),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    fn record_at(&self, endpoint: &str, status: StatusCode, headers: &HeaderMap, now: u64) {
+        let mut endpoints = self.lock();
+        let entry = endpoints.entry(endpoint.to_string()).or_default();
+        entry.limit = header_u64(headers, "x-ratelimit-limit").or(entry.limit);
+        entry.remaining = header_u64(headers, "x-ratelimit-remaining");
+        entry.reset_at = header_u64(headers, "x-ratelimit-reset").map(|reset| {
+            if reset >= EPOCH_RESET_THRESHOLD {
+                reset
+            } else {
+                now.saturating_add(reset)
+            }
+        });
+
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            entry.consecutive_limited = entry.consecutive_limited.saturating_add(1);
+            let backoff = retry_after_secs(headers, now)
+                .or_else(|| entry.reset_at.map(|reset| reset.saturating_sub(now)))
+                .unwrap_or_else(|| exponential_backoff(entry.consecutive_limited));
+            entry.blocked_until = Some(now.saturating_add(backoff.max(1)));
+        } else {
+            entry.consecutive_limited = 0;
+            entry.blocked_until = entry
+                .reset_at
+                .filter(|reset| entry.remaining == Some(0) && *reset > now);
+        }
+    }
+
+    fn status_at(&self, now: u64) -> Vec<EndpointStatus> {
+        let mut status = self
+            .lock()
+            .iter()
+            .map(|(endpoint, entry)| EndpointStatus {
+                endpoint: endpoint.clone(),
+                retry_after: entry
+                    .blocked_until
+                    .filter(|until| *until > now)
+                    .map(|until| until - now),
+                consecutive_limited: entry.consecutive_limited,
+                limit: entry.limit,
+                remaining: entry.remaining,
+                reset_at: entry.reset_at,
+            })
+            .collect::<Vec<_>>();
+        status.sort_by(|a, b| a.endpoint.cmp(&b.endpoint));
+        status
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, Endpoint>> {
+        self.endpoints.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+type Flight<T> = Arc<OnceCell<Result<T, FlavortimeError>>>;
+
+pub struct SingleFlight<T> {
+    inflight: Mutex<HashMap<String, Flight<T>>>,
+}
+
+impl<T> Default for SingleFlight<T> {
+    fn default() -> Self {
+        Self {
+            inflight: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl<T: Clone> SingleFlight<T> {
+    pub async fn run<F, Fut>(&self, key: String, fetch: F) -> Result<T, FlavortimeError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, FlavortimeError>>,
+    {
+        let flight = self.lock().entry(key.clone()).or_default().clone();
+        let result = flight.get_or_init(fetch).await.clone();
+
+        let mut inflight = self.lock();
+        if inflight
+            .get(&key)
+            .is_some_and(|current| Arc::ptr_eq(current, &flight))
+        {
+            inflight.remove(&key);
+        }
+        result
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, Flight<T>>> {
+        self.inflight.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+pub fn retry_after_secs(headers: &HeaderMap, now: u64) -> Option<u64> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    value.parse::<u64>().ok().or_else(|| {
+        DateTime::parse_from_rfc2822(value)
+            .ok()
+            .map(|date| u64::try_from(date.timestamp()).unwrap_or_default())
+            .map(|date| date.saturating_sub(now))
+    })
+}
+
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs())
+}
+
+fn header_u64(headers: &HeaderMap, name: &str) -> Option<u64> {
+    headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<u64>().ok())
+}
+
+fn exponential_backoff(consecutive_limited: u32) -> u64 {
+    let exponent = consecutive_limited.saturating_sub(1).min(10);
+    BASE_BACKOFF_SECS
+        .saturating_mul(1 << exponent)
+        .min(MAX_BACKOFF_SECS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use tauri::async_runtime::block_on;
+
+    const ENDPOINT: &str = "GET https://hackatime.test/api/v1/users/U1/projects/details";
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(*name, HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn honors_retry_after_seconds_and_dates() {
+        let limiter = RateLimiter::default();
+        limiter.record_at(
+            ENDPOINT,
+            StatusCode::TOO_MANY_REQUESTS,
+            &headers(&[("retry-after", "45")]),
+            1_000,
+        );
+        assert_eq!(
+            limiter.check_at(ENDPOINT, 1_010),
+            Err(FlavortimeError::RateLimited {
+                retry_after: Some(35)
+            })
+        );
+        assert!(limiter.check_at(ENDPOINT, 1_045).is_ok());
+        assert!(limiter.check_at("GET https://other.test/", 1_010).is_ok());
+
+        let date = headers(&[("retry-after", "Thu, 01 Jan 1970 00:20:00 GMT")]);
+        assert_eq!(retry_after_secs(&date, 1_000), Some(200));
+    }
+
+    #[test]
+    fn backs_off_exponentially_without_hints() {
+        let limiter = RateLimiter::default();
+        let none = HeaderMap::new();
+        limiter.record_at(ENDPOINT, StatusCode::TOO_MANY_REQUESTS, &none, 0);
+        limiter.record_at(ENDPOINT, StatusCode::TOO_MANY_REQUESTS, &none, 0);
+        limiter.record_at(ENDPOINT, StatusCode::TOO_MANY_REQUESTS, &none, 0);
+
+        let status = limiter.status_at(0);
+        assert_eq!(status[0].consecutive_limited, 3);
+        assert_eq!(status[0].retry_after, Some(BASE_BACKOFF_SECS * 4));
+
+        limiter.record_at(ENDPOINT, StatusCode::OK, &none, 500);
+        let status = limiter.status_at(500);
+        assert_eq!(status[0].consecutive_limited, 0);
+        assert_eq!(status[0].retry_after, None);
+        assert_eq!(exponential_backoff(40), MAX_BACKOFF_SECS);
+    }
+
+    #[test]
+    fn exhausted_quota_blocks_until_reset() {
+        let limiter = RateLimiter::default();
+        limiter.record_at(
+            ENDPOINT,
+            StatusCode::OK,
+            &headers(&[
+                ("x-ratelimit-limit", "100"),
+                ("x-ratelimit-remaining", "0"),
+                ("x-ratelimit-reset", "60"),
+            ]),
+            1_000,
+        );
+        let status = limiter.status_at(1_000);
+        assert_eq!(status[0].limit, Some(100));
+        assert_eq!(status[0].remaining, Some(0));
+        assert_eq!(status[0].reset_at, Some(1_060));
+        assert!(limiter.check_at(ENDPOINT, 1_030).is_err());
+        assert!(limiter.check_at(ENDPOINT, 1_060).is_ok());
+    }
+
+    #[test]
+    fn single_flight_shares_concurrent_results() {
+        let flights = SingleFlight::<u32>::default();
+        let calls = AtomicU32::new(0);
+        let fetch = || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            Ok(7)
+        };
+
+        let results = std::thread::scope(|scope| {
+            let handles = [
+                scope.spawn(|| block_on(flights.run("window".to_string(), fetch))),
+                scope.spawn(|| block_on(flights.run("window".to_string(), fetch))),
+            ];
+            handles.map(|handle| handle.join().unwrap())
+        });
+        assert_eq!(results, [Ok(7), Ok(7)]);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        let c = block_on(flights.run("window".to_string(), fetch));
+        assert_eq!(c, Ok(7));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,455 @@
+use super::api::{ApiFuture, HackatimeApi};
+use super::hackatime::{CodingBlock, ProjectDetails};
+use super::http::{HttpClient, DEFAULT_TIMEOUT};
+use crate::error::FlavortimeError;
+use chrono::{DateTime, Duration, NaiveDate, SecondsFormat, Utc};
+use serde::{de::DeserializeOwned, Deserialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+#[derive(Deserialize)]
+struct ProjectTotal {
+    name: String,
+    #[serde(default)]
+    total_seconds: f64,
+}
+
+#[derive(Default, Deserialize)]
+struct DaySummary {
+    #[serde(default)]
+    projects: Vec<ProjectTotal>,
+}
+
+#[derive(Deserialize)]
+struct SummariesResponse {
+    #[serde(default)]
+    data: Vec<DaySummary>,
+}
+
+#[derive(Deserialize)]
+struct StatusBarResponse {
+    data: DaySummary,
+}
+
+#[derive(Deserialize)]
+struct DurationEntry {
+    #[serde(default)]
+    project: String,
+    time: f64,
+    #[serde(default)]
+    duration: f64,
+}
+
+#[derive(Deserialize)]
+struct DurationsResponse {
+    #[serde(default)]
+    data: Vec<DurationEntry>,
+}
+
+#[derive(Default)]
+struct DayTotals {
+    seconds: HashMap<String, f64>,
+    last_active: HashMap<String, f64>,
+}
+
+pub struct WakaTime {
+    http: HttpClient,
+    base_url: String,
+    api_key: String,
+    durations_unsupported: AtomicBool,
+}
+
+impl WakaTime {
+    pub fn new(http: HttpClient, base_url: &str, api_key: &str) -> Self {
+        Self {
+            http,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key: api_key.trim().to_string(),
+            durations_unsupported: AtomicBool::new(false),
+        }
+    }
+
+    // One summaries call covers every day of the window. Servers without it
+    // still answer `status_bar/today`, which only knows about today.
+    async fn day_summaries(
+        &self,
+        first_day: NaiveDate,
+        last_day: NaiveDate,
+    ) -> Result<Vec<DaySummary>, FlavortimeError> {
+        let url = format!(
+            "{}/users/current/summaries?start={}&end={}&timezone=UTC",
+            self.base_url,
+            first_day.format("%Y-%m-%d"),
+            last_day.format("%Y-%m-%d")
+        );
+        match self
+            .get::<SummariesResponse>(url, "Failed to get summaries")
+            .await
+        {
+            Ok(body) => Ok(body.data),
+            Err(FlavortimeError::NotFound(_)) if last_day == Utc::now().date_naive() => {
+                let url = format!("{}/users/current/status_bar/today", self.base_url);
+                let body = self
+                    .get::<StatusBarResponse>(url, "Failed to get status bar")
+                    .await?;
+                let mut days: Vec<DaySummary> = std::iter::repeat_with(DaySummary::default)
+                    .take((last_day - first_day).num_days().max(0) as usize)
+                    .collect();
+                days.push(body.data);
+                Ok(days)
+            }
+            Err(FlavortimeError::NotFound(_)) => Ok(Vec::new()),
+            Err(err) => Err(err),
+        }
+    }
+
+    // Durations only refine days the window cuts through, and are skipped for
+    // good once the server shows it does not serve them.
+    async fn clipped_day(
+        &self,
+        day: NaiveDate,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Option<DayTotals> {
+        match self.day_durations(day).await {
+            Ok(Some(entries)) => Some(clip(entries, start, end)),
+            Ok(None) => None,
+            Err(err) => {
+                log::warn!("Using WakaTime day totals without durations: {err}");
+                None
+            }
+        }
+    }
+
+    async fn day_durations(
+        &self,
+        day: NaiveDate,
+    ) -> Result<Option<Vec<DurationEntry>>, FlavortimeError> {
+        if self.durations_unsupported.load(Ordering::Relaxed) {
+            return Ok(None);
+        }
+        let url = format!(
+            "{}/users/current/durations?date={}&timezone=UTC",
+            self.base_url,
+            day.format("%Y-%m-%d")
+        );
+        match self
+            .get::<DurationsResponse>(url, "Failed to get durations")
+            .await
+        {
+            Ok(body) => Ok(Some(body.data)),
+            Err(FlavortimeError::NotFound(_)) => {
+                self.durations_unsupported.store(true, Ordering::Relaxed);
+                Ok(None)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    async fn get<T: DeserializeOwned>(
+        &self,
+        url: String,
+        context: &str,
+    ) -> Result<T, FlavortimeError> {
+        let request = self.http.get(url).basic_auth(&self.api_key, None::<&str>);
+        let response = self.http.send(request, DEFAULT_TIMEOUT).await?;
+        if !response.status().is_success() {
+            return Err(FlavortimeError::from_response(response, context).await);
+        }
+        Ok(response.json::<T>().await?)
+    }
+}
+
+impl HackatimeApi for WakaTime {
+    fn user_projects_details<'a>(
+        &'a self,
+        _username: &'a str,
+        start_date: Option<&'a str>,
+        end_date: Option<&'a str>,
+    ) -> ApiFuture<'a, Vec<ProjectDetails>> {
+        Box::pin(async move {
+            if self.api_key.is_empty() {
+                return Err(FlavortimeError::InvalidInput(
+                    "Missing WakaTime API key".to_string(),
+                ));
+            }
+            let end = end_date.map_or(Ok(Utc::now()), parse_bound)?;
+            let start = start_date.map_or(Ok(end - Duration::hours(24)), parse_bound)?;
+            if end <= start {
+                return Ok(Vec::new());
+            }
+            let first_day = start.date_naive();
+            let last_day = (end - Duration::seconds(1)).date_naive();
+
+            let mut totals = DayTotals::default();
+            let summaries = self.day_summaries(first_day, last_day).await?;
+            for (day, summary) in first_day.iter_days().zip(summaries) {
+                let cut = start > midnight(day) || end < midnight(day + Duration::days(1));
+                let clipped = if cut {
+                    self.clipped_day(day, start, end).await
+                } else {
+                    None
+                };
+                match clipped {
+                    Some(clipped) => totals.merge(clipped),
+                    None => {
+                        for project in summary.projects {
+                            *totals.seconds.entry(project.name).or_default() +=
+                                project.total_seconds.max(0.0);
+                        }
+                    }
+                }
+            }
+            Ok(totals.into_project_details())
+        })
+    }
+
+    fn coding_blocks<'a>(
+        &'a self,
+        start_date: &'a str,
+        end_date: &'a str,
+    ) -> ApiFuture<'a, Option<Vec<CodingBlock>>> {
+        Box::pin(async move {
+            let (start, end) = (parse_bound(start_date)?, parse_bound(end_date)?);
+            let mut blocks = Vec::new();
+            if end <= start {
+                return Ok(Some(blocks));
+            }
+            let last_day = (end - Duration::seconds(1)).date_naive();
+            for day in start
+                .date_naive()
+                .iter_days()
+                .take_while(|day| *day <= last_day)
+            {
+                let Some(entries) = self.day_durations(day).await? else {
+                    return Ok(None);
+                };
+                blocks.extend(to_blocks(entries, start, end));
+            }
+            Ok(Some(blocks))
+        })
+    }
+}
+
+impl DayTotals {
+    fn merge(&mut self, other: DayTotals) {
+        for (name, seconds) in other.seconds {
+            *self.seconds.entry(name).or_default() += seconds;
+        }
+        for (name, at) in other.last_active {
+            let last_active = self.last_active.entry(name).or_default();
+            *last_active = last_active.max(at);
+        }
+    }
+
+    fn into_project_details(self) -> Vec<ProjectDetails> {
+        let last_active = self.last_active;
+        self.seconds
+            .into_iter()
+            .filter(|(_, total_seconds)| *total_seconds > 0.0)
+            .map(|(name, total_seconds)| ProjectDetails {
+                most_recent_heartbeat: last_active
+                    .get(&name)
+                    .and_then(|at| DateTime::from_timestamp(*at as i64, 0))
+                    .map(|at| at.to_rfc3339_opts(SecondsFormat::Secs, true)),
+                last_heartbeat: None,
+                languages: Vec::new(),
+                name,
+                total_seconds,
+            })
+            .collect()
+    }
+}
+
+fn parse_bound(value: &str) -> Result<DateTime<Utc>, FlavortimeError> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|value| value.with_timezone(&Utc))
+        .map_err(|_| FlavortimeError::InvalidInput(format!("Invalid time: {value}")))
+}
+
+fn midnight(day: NaiveDate) -> DateTime<Utc> {
+    day.and_time(chrono::NaiveTime::MIN).and_utc()
+}
+
+fn to_blocks(
+    entries: Vec<DurationEntry>,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Vec<CodingBlock> {
+    let (start, end) = (start.timestamp() as f64, end.timestamp() as f64);
+    entries
+        .into_iter()
+        .filter_map(|entry| {
+            let from = entry.time.max(start);
+            let until = (entry.time + entry.duration.max(0.0)).min(end);
+            (until > from).then_some(CodingBlock {
+                project: entry.project,
+                start_unix: from as i64,
+                end_unix: until as i64,
+            })
+        })
+        .collect()
+}
+
+fn clip(entries: Vec<DurationEntry>, start: DateTime<Utc>, end: DateTime<Utc>) -> DayTotals {
+    let mut totals = DayTotals::default();
+    for block in to_blocks(entries, start, end) {
+        let seconds = (block.end_unix - block.start_unix) as f64;
+        *totals.seconds.entry(block.project.clone()).or_default() += seconds;
+        let last_active = totals.last_active.entry(block.project).or_default();
+        *last_active = last_active.max(block.end_unix as f64);
+    }
+    totals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::hackatime::latest_project;
+    use crate::services::mock::{
+        test_server, MOCK_SLACK_ID, MOCK_STATUS_BAR_KEY, MOCK_WAKATIME_KEY,
+    };
+    use tauri::async_runtime::block_on;
+
+    fn wakatime(api_key: &str) -> WakaTime {
+        let base_url = format!("{}/api/v1", test_server().base_url());
+        WakaTime::new(HttpClient::new(None), &base_url, api_key)
+    }
+
+    fn entry(project: &str, time: i64, duration: f64) -> DurationEntry {
+        DurationEntry {
+            project: project.to_string(),
+            time: time as f64,
+            duration,
+        }
+    }
+
+    #[test]
+    fn durations_are_clipped_to_the_window() {
+        let start = DateTime::from_timestamp(10_000, 0).unwrap();
+        let end = DateTime::from_timestamp(20_000, 0).unwrap();
+        let mut projects = clip(
+            vec![
+                entry("api", 9_000, 2_000.0),
+                entry("api", 15_000, 500.0),
+                entry("web", 19_500, 1_000.0),
+                entry("old", 1_000, 600.0),
+            ],
+            start,
+            end,
+        )
+        .into_project_details();
+        projects.sort_by(|a, b| a.name.cmp(&b.name));
+
+        assert_eq!(projects.len(), 2);
+        assert_eq!(projects[0].name, "api");
+        assert_eq!(projects[0].total_seconds, 1_500.0);
+        assert_eq!(
+            projects[0].most_recent_heartbeat.as_deref(),
+            Some("1970-01-01T04:18:20Z")
+        );
+        assert_eq!(projects[1].total_seconds, 500.0);
+        assert_eq!(
+            projects[1].most_recent_heartbeat.as_deref(),
+            Some("1970-01-01T05:33:20Z")
+        );
+    }
+
+    fn totals(api_key: &str, start: Option<&str>, end: Option<&str>) -> Vec<(String, f64)> {
+        let projects = block_on(wakatime(api_key).user_projects_details(MOCK_SLACK_ID, start, end))
+            .expect("projects");
+        let mut totals: Vec<(String, f64)> = projects
+            .into_iter()
+            .map(|project| (project.name, project.total_seconds))
+            .collect();
+        totals.sort_by(|a, b| a.0.cmp(&b.0));
+        totals
+    }
+
+    #[test]
+    fn whole_days_come_from_summaries() {
+        assert_eq!(
+            totals(
+                MOCK_WAKATIME_KEY,
+                Some("2026-03-01T00:00:00Z"),
+                Some("2026-03-03T00:00:00Z")
+            ),
+            [
+                ("dotfiles".to_string(), 3_600.0),
+                ("flavortime".to_string(), 7_200.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn falls_back_to_status_bar_today() {
+        assert_eq!(
+            totals(MOCK_STATUS_BAR_KEY, None, None),
+            [
+                ("dotfiles".to_string(), 1_800.0),
+                ("flavortime".to_string(), 3_600.0)
+            ]
+        );
+        assert!(totals(
+            MOCK_STATUS_BAR_KEY,
+            Some("2026-03-01T00:00:00Z"),
+            Some("2026-03-02T00:00:00Z")
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn window_spans_and_clips_utc_days() {
+        let mut projects = block_on(wakatime(MOCK_WAKATIME_KEY).user_projects_details(
+            MOCK_SLACK_ID,
+            Some("2026-03-01T22:00:00Z"),
+            Some("2026-03-02T10:30:00Z"),
+        ))
+        .expect("projects");
+        projects.sort_by(|a, b| a.name.cmp(&b.name));
+
+        assert_eq!(projects.len(), 2);
+        assert_eq!(projects[0].name, "dotfiles");
+        assert_eq!(projects[0].total_seconds, 1_800.0);
+        assert_eq!(projects[1].name, "flavortime");
+        assert_eq!(projects[1].total_seconds, 1_800.0);
+        assert_eq!(
+            projects[1].most_recent_heartbeat.as_deref(),
+            Some("2026-03-02T10:30:00Z")
+        );
+        let (name, _) = latest_project(&projects, None).expect("latest project");
+        assert_eq!(name, "flavortime");
+    }
+
+    #[test]
+    fn sends_basic_auth() {
+        let err = block_on(wakatime("wrong-key").user_projects_details(
+            MOCK_SLACK_ID,
+            Some("2026-03-01T00:00:00Z"),
+            Some("2026-03-02T00:00:00Z"),
+        ))
+        .err()
+        .expect("unauthorized");
+        assert!(matches!(err, FlavortimeError::Unauthorized(_)));
+    }
+
+    #[test]
+    fn coding_blocks_come_from_durations() {
+        let blocks = block_on(
+            wakatime(MOCK_WAKATIME_KEY)
+                .coding_blocks("2026-03-01T10:30:00Z", "2026-03-02T00:00:00Z"),
+        )
+        .expect("blocks")
+        .expect("durations supported");
+        let projects: Vec<&str> = blocks.iter().map(|block| block.project.as_str()).collect();
+        assert_eq!(projects, ["flavortime", "dotfiles"]);
+        assert_eq!(blocks[0].end_unix - blocks[0].start_unix, 1_800);
+
+        let status_bar_only = block_on(
+            wakatime(MOCK_STATUS_BAR_KEY)
+                .coding_blocks("2026-03-01T00:00:00Z", "2026-03-02T00:00:00Z"),
+        )
+        .expect("blocks");
+        assert!(status_bar_only.is_none());
+    }
+}
@@ -1,26 +1,108 @@
+use crate::app::commands::{
+    activate_presence_profile_from_state, emit_presence_profiles, history_summary,
+    presence_profiles, HackatimeData, HistorySummary,
+};
+use crate::app::scheduler::PRESENCE_PROFILES_EVENT;
 use crate::app::state::AppState;
-use crate::data::config::Mode;
-use crate::services::hackatime::{latest_project, rolling_24h_window, Hackatime};
+use crate::data::config::{Mode, WindowMode};
 use std::time::Duration;
 use tauri::{
     image::Image,
-    menu::{MenuBuilder, MenuItemBuilder, PredefinedMenuItem},
+    menu::{
+        CheckMenuItemBuilder, MenuBuilder, MenuItemBuilder, PredefinedMenuItem, Submenu,
+        SubmenuBuilder,
+    },
     tray::{TrayIconBuilder, TrayIconEvent},
-    App, AppHandle, Manager, WebviewWindow,
+    App, AppHandle, Listener, Manager, WebviewWindow,
 };
 
 #[cfg(target_os = "macos")]
 const MACOS_TRAY_ICON_TEMPLATE: &[u8] = include_bytes!("../icons/trayTemplate.png");
 const NO_DATA_TEXT: &str = "No data yet";
 const NO_PROJECT_TEXT: &str = "No active project";
+const OFFLINE_TEXT: &str = "Hackatime unreachable";
+const NO_PROFILES_TEXT: &str = "No profiles yet";
+const NO_STREAK_TEXT: &str = "No history yet";
+const PROFILE_ITEM_PREFIX: &str = "profile:";
 
-fn format_hours(total_seconds: f64) -> String {
+fn format_hours(total_seconds: f64, window_mode: &WindowMode) -> String {
     let total_minutes = (total_seconds.max(0.0) / 60.0).floor() as u32;
     let hours = total_minutes / 60;
     let minutes = total_minutes % 60;
+    let period = match window_mode {
+        WindowMode::Rolling24h => "in the last 24h",
+        WindowMode::LocalDay | WindowMode::Custom { .. } => "today",
+    };
     match hours {
-        0 => format!("{minutes}m today"),
-        _ => format!("{hours}h {minutes}m today"),
+        0 => format!("{minutes}m {period}"),
+        _ => format!("{hours}h {minutes}m {period}"),
+    }
+}
+
+fn snapshot_texts(data: &HackatimeData) -> (String, String) {
+    let time_text = format_hours(data.total_hours * 3600.0, &data.window_mode);
+    let time_text = if data.stale {
+        format!("{time_text} (offline)")
+    } else {
+        time_text
+    };
+    let project_text = data
+        .current_project
+        .as_ref()
+        .map(|project| match project.public_name.as_deref() {
+            Some(public) if public == project.name => format!("Working on {}", project.name),
+            Some(public) => format!("Working on {} (shown as {public})", project.name),
+            None => format!("Working on {} (hidden on Discord)", project.name),
+        })
+        .unwrap_or_else(|| NO_PROJECT_TEXT.into());
+    (time_text, project_text)
+}
+
+fn refresh_profiles_menu(app: &AppHandle, submenu: &Submenu<tauri::Wry>) -> tauri::Result<()> {
+    for item in submenu.items()? {
+        submenu.remove(&item)?;
+    }
+
+    let profiles = app
+        .try_state::<AppState>()
+        .and_then(|state| state.config.lock().ok().map(|cfg| presence_profiles(&cfg)));
+    let Some(profiles) = profiles.filter(|profiles| !profiles.profiles.is_empty()) else {
+        let empty = MenuItemBuilder::with_id("profiles-empty", NO_PROFILES_TEXT)
+            .enabled(false)
+            .build(app)?;
+        return submenu.append(&empty);
+    };
+
+    for name in &profiles.profiles {
+        let item = CheckMenuItemBuilder::with_id(format!("{PROFILE_ITEM_PREFIX}{name}"), name)
+            .checked(profiles.active.as_ref() == Some(name))
+            .build(app)?;
+        submenu.append(&item)?;
+    }
+    Ok(())
+}
+
+fn activate_profile(app: &AppHandle, name: &str) {
+    let Some(state) = app.try_state::<AppState>() else {
+        return;
+    };
+    match activate_presence_profile_from_state(&state, Some(name)) {
+        Ok(profiles) => emit_presence_profiles(app, &profiles),
+        Err(err) => log::warn!("Failed to switch to presence profile {name}: {err}"),
+    }
+}
+
+fn streak_text(summary: &HistorySummary) -> String {
+    let streaks = &summary.streaks;
+    let goal = if streaks.goal_met_today {
+        "goal met today"
+    } else {
+        "goal not met yet"
+    };
+    match streaks.current {
+        0 => format!("No streak (best {}) · {goal}", streaks.longest),
+        1 => format!("1-day streak (best {}) · {goal}", streaks.longest),
+        days => format!("{days}-day streak (best {}) · {goal}", streaks.longest),
     }
 }
 
@@ -51,7 +133,13 @@ pub fn setup(app: &App) -> tauri::Result<()> {
     let project_item = MenuItemBuilder::with_id("project", "No active project")
         .enabled(false)
         .build(handle)?;
+    let streak_item = MenuItemBuilder::with_id("streak", NO_STREAK_TEXT)
+        .enabled(false)
+        .build(handle)?;
     let separator = PredefinedMenuItem::separator(handle)?;
+    let profiles_menu = SubmenuBuilder::with_id(handle, "profiles", "Presence Profile").build()?;
+    refresh_profiles_menu(handle, &profiles_menu)?;
+    let profiles_separator = PredefinedMenuItem::separator(handle)?;
     let show_item = MenuItemBuilder::with_id("show", "Show Flavortime").build(handle)?;
     let quit_item = MenuItemBuilder::with_id("quit", "Quit").build(handle)?;
 
@@ -59,7 +147,10 @@ pub fn setup(app: &App) -> tauri::Result<()> {
         .items(&[
             &time_item,
             &project_item,
+            &streak_item,
             &separator,
+            &profiles_menu,
+            &profiles_separator,
             &show_item,
             &quit_item,
         ])
@@ -87,7 +178,11 @@ pub fn setup(app: &App) -> tauri::Result<()> {
         .on_menu_event(|app, event| match event.id().as_ref() {
             "show" => restore_main_window(app),
             "quit" => app.exit(0),
-            _ => {}
+            id => {
+                if let Some(name) = id.strip_prefix(PROFILE_ITEM_PREFIX) {
+                    activate_profile(app, name);
+                }
+            }
         })
         .on_tray_icon_event(|tray, event| {
             if let TrayIconEvent::Click { .. } = event {
@@ -107,6 +202,13 @@ pub fn setup(app: &App) -> tauri::Result<()> {
     let tray_builder = tray_builder.icon_as_template(true);
     let tray = tray_builder.build(app)?;
 
+    let profiles_handle = handle.clone();
+    handle.listen(PRESENCE_PROFILES_EVENT, move |_| {
+        if let Err(err) = refresh_profiles_menu(&profiles_handle, &profiles_menu) {
+            log::warn!("Failed to refresh presence profiles menu: {err}");
+        }
+    });
+
     if let Some(window) = app.get_webview_window("main") {
         #[cfg(not(target_os = "macos"))]
         {
@@ -132,52 +234,58 @@ pub fn setup(app: &App) -> tauri::Result<()> {
     tauri::async_runtime::spawn(async move {
         let mut last_time_text = NO_DATA_TEXT.to_string();
         let mut last_project_text = NO_PROJECT_TEXT.to_string();
+        let mut snapshots = None;
 
         loop {
             let auth = app_handle.try_state::<AppState>().and_then(|state| {
+                let snapshots = snapshots.get_or_insert_with(|| state.snapshots.subscribe());
+                let update = snapshots.borrow_and_update().clone();
+                let history = history_summary(&state)
+                    .ok()
+                    .filter(|summary| !summary.days.is_empty());
                 let cfg = state.config.lock().ok()?;
-                Some((cfg.auth_mode.clone(), cfg.slack_id.clone()))
+                Some((cfg.auth_mode.clone(), cfg.slack_id.clone(), update, history))
             });
 
             match auth {
-                Some((Mode::Hackatime, Some(slack_id))) => {
-                    let window = rolling_24h_window();
-
-                    if let Ok(projects) = Hackatime::user_projects_details(
-                        &slack_id,
-                        Some(window.start_rfc3339.as_str()),
-                        Some(window.end_rfc3339.as_str()),
-                    )
-                    .await
-                    {
-                        let total_seconds = projects
-                            .iter()
-                            .map(|project| project.total_seconds.max(0.0))
-                            .sum::<f64>();
-                        last_time_text = format_hours(total_seconds);
-                        last_project_text = latest_project(&projects, None)
-                            .map(|(project, _)| format!("Working on {project}"))
-                            .unwrap_or_else(|| "No active project".into());
+                Some((Mode::Hackatime, Some(_), update, history)) => {
+                    match update {
+                        Some(Ok(data)) => {
+                            (last_time_text, last_project_text) = snapshot_texts(&data);
+                        }
+                        Some(Err(_)) => {
+                            last_time_text = OFFLINE_TEXT.to_string();
+                            last_project_text = NO_PROJECT_TEXT.to_string();
+                        }
+                        None => {}
                     }
 
                     let _ = time_item.set_text(&last_time_text);
                     let _ = project_item.set_text(&last_project_text);
+                    let _ = streak_item.set_text(
+                        history
+                            .as_ref()
+                            .map_or(NO_STREAK_TEXT.to_string(), streak_text),
+                    );
                     let _ = tray.set_tooltip(Some(&format!("Flavortime — {}", last_time_text)));
                 }
-                Some((Mode::Adult, _)) => {
+                Some((Mode::Adult, _, _, _)) => {
                     reset_status_texts(&mut last_time_text, &mut last_project_text);
+                    let _ = streak_item.set_text(NO_STREAK_TEXT);
                     let _ = time_item.set_text("Adult mode (no Hackatime)");
                     let _ = project_item.set_text(NO_PROJECT_TEXT);
                     let _ = tray.set_tooltip(Some("Flavortime"));
                 }
-                Some((Mode::Hackatime, None)) => {
+                Some((Mode::Hackatime, None, _, _)) => {
                     reset_status_texts(&mut last_time_text, &mut last_project_text);
+                    let _ = streak_item.set_text(NO_STREAK_TEXT);
                     let _ = time_item.set_text("Flavortime disconnected");
                     let _ = project_item.set_text("Open Flavortime to reconnect");
                     let _ = tray.set_tooltip(Some("Flavortime"));
                 }
-                Some((Mode::None, _)) => {
+                Some((Mode::None, _, _, _)) => {
                     reset_status_texts(&mut last_time_text, &mut last_project_text);
+                    let _ = streak_item.set_text(NO_STREAK_TEXT);
                     let _ = time_item.set_text("Not signed in");
                     let _ = project_item.set_text(NO_PROJECT_TEXT);
                     let _ = tray.set_tooltip(Some("Flavortime"));
@@ -191,7 +299,11 @@ pub fn setup(app: &App) -> tauri::Result<()> {
                 }
             }
 
-            tokio::time::sleep(Duration::from_secs(20)).await;
+            if let Some(snapshots) = snapshots.as_mut() {
+                let _ = tokio::time::timeout(Duration::from_secs(20), snapshots.changed()).await;
+            } else {
+                tokio::time::sleep(Duration::from_secs(20)).await;
+            }
         }
     });
 
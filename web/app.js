@@ -23,7 +23,13 @@ const elements = {
     loginUpdateAction: byId('login-update-action'),
     loginUpdateDismiss: byId('login-update-dismiss'),
     currentProject: byId('current-project'),
+    projectPublicName: byId('project-public-name'),
+    privacyRules: byId('privacy-rules'),
+    privacyAdd: byId('btn-privacy-add'),
+    privacySave: byId('btn-privacy-save'),
+    privacyError: byId('privacy-error'),
     totalHours: byId('total-hours'),
+    statsStale: byId('stats-stale'),
     rpcStatus: byId('rpc-status'),
     rpcDetail: byId('rpc-detail'),
     rpcRefreshButton: byId('btn-rpc-refresh'),
@@ -34,6 +40,57 @@ const elements = {
     showTime: byId('show-time'),
     launchStartup: byId('launch-startup'),
     appEnabled: byId('app-enabled'),
+    trackingBackend: byId('tracking-backend'),
+    wakatimeFields: byId('wakatime-fields'),
+    wakatimeUrl: byId('wakatime-url'),
+    wakatimeKey: byId('wakatime-key'),
+    trackingSave: byId('btn-tracking-save'),
+    trackingError: byId('tracking-error'),
+    maxStaleness: byId('max-staleness'),
+    dailyGoal: byId('daily-goal'),
+    historyDays: byId('history-days'),
+    streakSummary: byId('streak-summary'),
+    dashboardTabs: Array.from(document.querySelectorAll('.dashboard-tab')),
+    dashboardOverview: byId('dashboard-overview'),
+    dashboardStats: byId('dashboard-stats'),
+    statsRanges: Array.from(document.querySelectorAll('.stats-range')),
+    statsTotal: byId('stats-total'),
+    statsAverage: byId('stats-average'),
+    statsError: byId('stats-error'),
+    statsProjects: byId('stats-projects'),
+    statsDays: byId('stats-days'),
+    exportStart: byId('export-start'),
+    exportEnd: byId('export-end'),
+    exportFormat: byId('export-format'),
+    exportProjects: byId('export-projects'),
+    exportPath: byId('export-path'),
+    exportButton: byId('btn-export'),
+    exportResult: byId('export-result'),
+    sessionsStart: byId('sessions-start'),
+    sessionsEnd: byId('sessions-end'),
+    sessionsLoad: byId('btn-sessions-load'),
+    sessionsSummary: byId('sessions-summary'),
+    sessionsList: byId('sessions-list'),
+    presenceProjectHours: byId('presence-project-hours'),
+    windowMode: byId('window-mode'),
+    windowCustomFields: byId('window-custom-fields'),
+    windowOffset: byId('window-offset'),
+    windowDayStart: byId('window-day-start'),
+    windowError: byId('window-error'),
+    presenceProfile: byId('presence-profile'),
+    presenceProfileName: byId('presence-profile-name'),
+    profileCreate: byId('btn-profile-create'),
+    profileRename: byId('btn-profile-rename'),
+    profileDelete: byId('btn-profile-delete'),
+    profileError: byId('profile-error'),
+    presenceFields: byId('presence-fields'),
+    presencePreviewDetails: byId('presence-preview-details'),
+    presencePreviewState: byId('presence-preview-state'),
+    presencePreviewExtra: byId('presence-preview-extra'),
+    presenceSave: byId('btn-presence-save'),
+    presenceReset: byId('btn-presence-reset'),
+    presenceError: byId('presence-error'),
+    totalHoursLabel: byId('total-hours-label'),
     referralSelect: byId('referral-select'),
     customReferral: byId('custom-referral'),
     referralSection: byId('referral-selection'),
@@ -88,12 +145,18 @@ let rpcWaitingDots = 1;
 let rpcWaitingSinceMs = 0;
 let rpcRecoveryRunId = 0;
 let authTransitionInProgress = false;
+let statsRange = 'week';
 let authFlowRevision = 0;
 let updaterBusy = false;
 let updaterDownloadDismissed = false;
 let updaterDownloadPercent = null;
 let updaterProgressUnlisten = null;
 let updaterFinishUnlisten = null;
+let hackatimeDataUnlisten = null;
+let hackatimeDataFailedUnlisten = null;
+let heartbeatFailedUnlisten = null;
+let presenceProfilesUnlisten = null;
+let historyUnlisten = null;
 let lastRpcStatus = {
     connected: false,
     enabled: false,
@@ -984,6 +1047,7 @@ async function populateSettings(status) {
     elements.showTime.checked = status.show_time_tracking;
     elements.launchStartup.checked = status.launch_at_startup;
     elements.appEnabled.checked = status.app_enabled;
+    populateTrackingSettings(status);
 
     const selectedCode = renderReferralSelect(status);
     elements.customReferral.value = status.custom_referral_code || '';
@@ -998,6 +1062,262 @@ async function populateSettings(status) {
     }
 }
 
+function populateTrackingSettings(status) {
+    elements.trackingBackend.value = status.tracking_backend || 'hackatime';
+    elements.wakatimeUrl.value = status.wakatime_base_url || '';
+    elements.wakatimeKey.value = '';
+    elements.maxStaleness.value = status.max_snapshot_staleness_minutes ?? 30;
+    elements.dailyGoal.value = status.daily_goal_minutes ?? 60;
+    elements.historyDays.value = status.history_days ?? 30;
+    populateWindowMode(status.window_mode);
+    elements.presenceProjectHours.value = status.presence_project_hours || 'project';
+    elements.trackingError.classList.add('hidden');
+    updateTrackingFields();
+    populatePresenceTemplate(status.presence_template);
+    populatePresenceProfiles(status);
+    populatePrivacyRules(status.privacy_rules);
+}
+
+function buildSelect(options, value) {
+    const select = document.createElement('select');
+    select.className = 'select';
+    for (const [optionValue, labelKey] of options) {
+        const option = document.createElement('option');
+        option.value = optionValue;
+        option.textContent = t(labelKey);
+        select.appendChild(option);
+    }
+    select.value = value;
+    return select;
+}
+
+function addPrivacyRuleRow(rule = {}) {
+    const row = document.createElement('div');
+    row.className = 'referral-field privacy-rule';
+
+    const matcher = buildSelect([
+        ['exact', 'privacy.match_exact'],
+        ['glob', 'privacy.match_glob'],
+        ['regex', 'privacy.match_regex'],
+    ], rule.matcher || 'exact');
+    matcher.dataset.privacy = 'matcher';
+
+    const pattern = document.createElement('input');
+    pattern.type = 'text';
+    pattern.className = 'input';
+    pattern.placeholder = t('privacy.pattern_placeholder');
+    pattern.value = rule.pattern || '';
+    pattern.dataset.privacy = 'pattern';
+
+    const action = buildSelect([
+        ['secret', 'privacy.action_secret'],
+        ['alias', 'privacy.action_alias'],
+        ['hide', 'privacy.action_hide'],
+    ], rule.action || 'secret');
+    action.dataset.privacy = 'action';
+
+    const alias = document.createElement('input');
+    alias.type = 'text';
+    alias.className = 'input';
+    alias.placeholder = t('privacy.alias_placeholder');
+    alias.value = rule.alias || '';
+    alias.dataset.privacy = 'alias';
+    alias.classList.toggle('hidden', action.value !== 'alias');
+    action.addEventListener('change', () => {
+        alias.classList.toggle('hidden', action.value !== 'alias');
+    });
+
+    const remove = document.createElement('button');
+    remove.type = 'button';
+    remove.className = 'btn btn-small btn-danger';
+    remove.textContent = t('privacy.remove');
+    remove.addEventListener('click', () => row.remove());
+
+    row.append(matcher, pattern, action, alias, remove);
+    elements.privacyRules.appendChild(row);
+}
+
+function populatePrivacyRules(rules) {
+    elements.privacyRules.innerHTML = '';
+    for (const rule of rules || []) {
+        addPrivacyRuleRow(rule);
+    }
+    elements.privacyError.classList.add('hidden');
+}
+
+function readPrivacyRules() {
+    return Array.from(elements.privacyRules.querySelectorAll('.privacy-rule')).map((row) => {
+        const field = (name) => row.querySelector(`[data-privacy="${name}"]`).value;
+        const rule = { pattern: field('pattern'), matcher: field('matcher'), action: field('action') };
+        if (rule.action === 'alias') {
+            rule.alias = field('alias');
+        }
+        return rule;
+    });
+}
+
+async function savePrivacyRules() {
+    elements.privacyError.classList.add('hidden');
+    try {
+        await invoke('set_privacy_rules', { rules: readPrivacyRules() });
+    } catch (err) {
+        console.error('Error:', err);
+        elements.privacyError.textContent = formatLoginError(t('privacy.save_failed'), err);
+        elements.privacyError.classList.remove('hidden');
+    }
+}
+
+function populatePresenceProfiles(status) {
+    const select = elements.presenceProfile;
+    select.innerHTML = '';
+    const none = document.createElement('option');
+    none.value = '';
+    none.textContent = t('profiles.none');
+    select.appendChild(none);
+    for (const profile of status.presence_profiles || []) {
+        const option = document.createElement('option');
+        option.value = profile.name;
+        option.textContent = profile.name;
+        select.appendChild(option);
+    }
+    select.value = status.active_presence_profile || '';
+    elements.presenceProfileName.value = status.active_presence_profile || '';
+    elements.profileRename.disabled = !select.value;
+    elements.profileDelete.disabled = !select.value;
+    elements.profileError.classList.add('hidden');
+}
+
+async function updatePresenceProfiles(command, args) {
+    elements.profileError.classList.add('hidden');
+    try {
+        await invoke(command, args);
+        await populateSettings(await invoke('get_status'));
+    } catch (err) {
+        console.error('Error:', err);
+        elements.profileError.textContent = formatLoginError(t('profiles.failed'), err);
+        elements.profileError.classList.remove('hidden');
+    }
+}
+
+function presenceInputs() {
+    return Array.from(elements.presenceFields.querySelectorAll('[data-presence-field]'));
+}
+
+function populatePresenceTemplate(template) {
+    for (const input of presenceInputs()) {
+        input.value = template?.[input.dataset.presenceField] ?? '';
+    }
+    elements.presenceError.classList.add('hidden');
+    previewPresenceTemplate();
+}
+
+function readPresenceTemplate() {
+    const template = {};
+    for (const input of presenceInputs()) {
+        template[input.dataset.presenceField] = input.value;
+    }
+    return template;
+}
+
+function showPresenceError(message, err) {
+    elements.presenceError.textContent = formatLoginError(message, err);
+    elements.presenceError.classList.remove('hidden');
+}
+
+let presencePreviewTimer = null;
+
+function schedulePresencePreview() {
+    clearTimeout(presencePreviewTimer);
+    presencePreviewTimer = setTimeout(previewPresenceTemplate, 250);
+}
+
+async function previewPresenceTemplate() {
+    elements.presenceError.classList.add('hidden');
+    try {
+        const preview = await invoke('preview_presence', { template: readPresenceTemplate() });
+        elements.presencePreviewDetails.textContent = preview.details || '';
+        elements.presencePreviewState.textContent = preview.state || '';
+        const extra = [preview.large_text, preview.small_text];
+        if (preview.button_url && preview.button_label) {
+            extra.push(`${t('presence.button_preview')} ${preview.button_label}`);
+        }
+        elements.presencePreviewExtra.textContent = extra.filter(Boolean).join(' · ');
+    } catch (err) {
+        showPresenceError(t('presence.preview_failed'), err);
+    }
+}
+
+async function savePresenceTemplate(template) {
+    elements.presenceError.classList.add('hidden');
+    try {
+        await invoke('set_presence_template', { template });
+        const status = await invoke('get_status');
+        populatePresenceTemplate(status.presence_template);
+    } catch (err) {
+        console.error('Error:', err);
+        showPresenceError(t('presence.save_failed'), err);
+    }
+}
+
+function populateWindowMode(windowMode) {
+    const mode = windowMode?.mode || 'rolling_24h';
+    elements.windowMode.value = mode;
+    elements.windowOffset.value = mode === 'custom' ? formatUtcOffset(windowMode.utc_offset_minutes) : '';
+    elements.windowDayStart.value = mode === 'custom' ? windowMode.day_start_hour : 0;
+    elements.windowError.classList.add('hidden');
+    updateWindowFields();
+}
+
+function formatUtcOffset(minutes) {
+    const total = Number(minutes) || 0;
+    const sign = total < 0 ? '-' : '+';
+    const abs = Math.abs(total);
+    const hours = String(Math.floor(abs / 60)).padStart(2, '0');
+    const mins = String(abs % 60).padStart(2, '0');
+    return `${sign}${hours}:${mins}`;
+}
+
+function updateWindowFields() {
+    elements.windowCustomFields.classList.toggle('hidden', elements.windowMode.value !== 'custom');
+}
+
+async function saveWindowMode() {
+    const mode = elements.windowMode.value;
+    const utcOffset = elements.windowOffset.value.trim() || null;
+    const dayStartHour = mode === 'custom' ? Number(elements.windowDayStart.value) || 0 : null;
+    elements.windowError.classList.add('hidden');
+    try {
+        await invoke('set_window_mode', { mode, utcOffset, dayStartHour });
+    } catch (err) {
+        console.error('Error:', err);
+        elements.windowError.textContent = formatLoginError(t('tracking.window_failed'), err);
+        elements.windowError.classList.remove('hidden');
+    }
+}
+
+function updateTrackingFields() {
+    const custom = elements.trackingBackend.value === 'wakatime';
+    elements.wakatimeFields.classList.toggle('hidden', !custom);
+}
+
+async function saveTrackingBackend() {
+    const backend = elements.trackingBackend.value;
+    const baseUrl = elements.wakatimeUrl.value.trim() || null;
+    const apiKey = elements.wakatimeKey.value.trim() || null;
+    elements.trackingError.classList.add('hidden');
+    try {
+        await invoke('set_tracking_backend', { backend, baseUrl, apiKey });
+        elements.wakatimeKey.value = '';
+        if (!screens.hackatime.classList.contains('hidden')) {
+            loadHackatimeData({ showLoading: true });
+        }
+    } catch (err) {
+        console.error('Error:', err);
+        elements.trackingError.textContent = formatLoginError(t('tracking.save_failed'), err);
+        elements.trackingError.classList.remove('hidden');
+    }
+}
+
 function populateAdultSettings(status) {
     elements.adultReferralCode.value = status.custom_referral_code || '';
     const showReferral = status.show_referral_code !== false;
@@ -1072,33 +1392,315 @@ function setHackatimeStatsLoading() {
     setStatLoading(elements.totalHours);
 }
 
-async function loadHackatimeData({ showLoading = false } = {}) {
-    if (showLoading) {
-        setHackatimeStatsLoading();
+function renderHackatimeData(data) {
+    const project = data?.current_project;
+    setStatValue(
+        elements.currentProject,
+        project?.name ? `${project.name} (${formatHours(project.hours || 0)})` : t('dashboard.stat_empty')
+    );
+    setStatValue(elements.totalHours, formatHours(data?.total_hours || 0));
+    renderPublicName(project);
+    renderStaleNotice(data?.stale ? data.fetched_at : null);
+    const rolling = (data?.window_mode?.mode || 'rolling_24h') === 'rolling_24h';
+    elements.totalHoursLabel.textContent = t(rolling ? 'dashboard.stat_last_24h' : 'dashboard.stat_today');
+}
+
+function renderPublicName(project) {
+    const target = elements.projectPublicName;
+    if (!project?.name || project.public_name === project.name) {
+        target.classList.add('hidden');
+        return;
+    }
+    target.textContent = project.public_name
+        ? t('privacy.shown_as').replace('{name}', project.public_name)
+        : t('privacy.hidden');
+    target.classList.remove('hidden');
+}
+
+function renderHistory(summary) {
+    const target = elements.streakSummary;
+    if (!summary?.days?.length) {
+        target.classList.add('hidden');
+        return;
+    }
+    const streaks = summary.streaks;
+    let text = t('dashboard.streak')
+        .replace('{current}', streaks.current)
+        .replace('{longest}', streaks.longest)
+        .replace('{today}', formatHours(streaks.today_seconds / 3600))
+        .replace('{goal}', formatHours(summary.daily_goal_minutes / 60));
+    if (streaks.goal_met_today) {
+        text = `${text} · ${t('dashboard.streak_goal_met')}`;
+    }
+    target.textContent = text;
+    target.classList.remove('hidden');
+}
+
+async function loadHistory() {
+    try {
+        renderHistory(await invoke('get_history'));
+    } catch (err) {
+        console.error('Failed to load history:', err);
+    }
+}
+
+function showDashboardTab(tab) {
+    for (const button of elements.dashboardTabs) {
+        const active = button.dataset.tab === tab;
+        button.classList.toggle('active', active);
+        button.setAttribute('aria-selected', String(active));
+    }
+    elements.dashboardOverview.classList.toggle('hidden', tab !== 'overview');
+    elements.dashboardStats.classList.toggle('hidden', tab !== 'stats');
+    if (tab === 'stats') {
+        loadStats();
+    }
+}
+
+function statsBarRow(label, seconds, maxSeconds) {
+    const row = document.createElement('div');
+    row.className = 'stats-bar-row';
+
+    const name = document.createElement('span');
+    name.textContent = label;
+
+    const bar = document.createElement('div');
+    bar.className = 'stats-bar';
+    const fill = document.createElement('div');
+    fill.className = 'stats-bar-fill';
+    fill.style.width = `${maxSeconds > 0 ? Math.round((seconds / maxSeconds) * 100) : 0}%`;
+    bar.appendChild(fill);
+
+    const value = document.createElement('span');
+    value.textContent = formatHours(seconds / 3600);
+
+    row.append(name, bar, value);
+    return row;
+}
+
+function renderStats(report) {
+    elements.statsTotal.textContent = formatHours(report.total_seconds / 3600);
+    elements.statsAverage.textContent = formatHours(report.daily_average_seconds / 3600);
+
+    elements.statsProjects.innerHTML = '';
+    const topSeconds = report.top_projects[0]?.seconds || 0;
+    for (const project of report.top_projects) {
+        elements.statsProjects.appendChild(statsBarRow(project.name, project.seconds, topSeconds));
+    }
+    if (!report.top_projects.length) {
+        const empty = document.createElement('p');
+        empty.className = 'referral-hint';
+        empty.textContent = t('stats.empty');
+        elements.statsProjects.appendChild(empty);
+    }
+
+    elements.statsDays.innerHTML = '';
+    const daySeconds = Math.max(0, ...report.days.map((day) => day.seconds));
+    for (const day of [...report.days].reverse()) {
+        const row = statsBarRow(day.date.slice(5), day.seconds, daySeconds);
+        if (day.top_project) {
+            row.title = day.top_project;
+        }
+        elements.statsDays.appendChild(row);
+    }
+}
+
+async function loadStats() {
+    elements.statsError.classList.add('hidden');
+    for (const button of elements.statsRanges) {
+        button.classList.toggle('active', button.dataset.statsRange === statsRange);
+    }
+    try {
+        renderStats(await invoke('get_stats', { range: statsRange }));
+    } catch (err) {
+        console.error('Failed to load stats:', err);
+        elements.statsError.textContent = formatLoginError(t('stats.load_failed'), err);
+        elements.statsError.classList.remove('hidden');
+    }
+}
+
+async function exportHistory() {
+    if (!elements.exportStart.value || !elements.exportEnd.value) {
+        const today = new Date();
+        const monthAgo = new Date(today);
+        monthAgo.setDate(today.getDate() - 29);
+        elements.exportStart.value = elements.exportStart.value || localDateInput(monthAgo);
+        elements.exportEnd.value = elements.exportEnd.value || localDateInput(today);
     }
+    const projects = elements.exportProjects.value
+        .split(',')
+        .map((name) => name.trim())
+        .filter(Boolean);
 
+    elements.exportButton.disabled = true;
+    elements.exportResult.classList.add('hidden');
     try {
-        const data = await invoke('get_hackatime_data');
+        const summary = await invoke('export_history', {
+            startDate: elements.exportStart.value,
+            endDate: elements.exportEnd.value,
+            format: elements.exportFormat.value,
+            path: elements.exportPath.value,
+            projects,
+        });
+        elements.exportResult.textContent = t('export.done')
+            .replace('{entries}', summary.entries)
+            .replace('{days}', summary.days)
+            .replace('{path}', summary.path);
+    } catch (err) {
+        console.error('Export failed:', err);
+        elements.exportResult.textContent = formatLoginError(t('export.failed'), err);
+    } finally {
+        elements.exportButton.disabled = false;
+        elements.exportResult.classList.remove('hidden');
+    }
+}
+
+function localDateInput(date) {
+    const pad = (value) => String(value).padStart(2, '0');
+    return `${date.getFullYear()}-${pad(date.getMonth() + 1)}-${pad(date.getDate())}`;
+}
 
-        setStatValue(elements.currentProject, data.current_project?.name || t('dashboard.stat_empty'));
-        setStatValue(elements.totalHours, formatHours(data.total_hours));
+function renderSessions(report) {
+    elements.sessionsList.innerHTML = '';
+    elements.sessionsSummary.textContent = t('sessions.summary')
+        .replace('{count}', report.sessions.length)
+        .replace('{shared}', formatHours(report.sharing_seconds / 3600))
+        .replace('{discord}', formatHours(report.discord_seconds / 3600));
+    elements.sessionsSummary.classList.remove('hidden');
 
-        await invoke('update_discord_presence', {
-            project: data.current_project?.name || null,
-            hours: data.total_hours
+    for (const session of [...report.sessions].reverse()) {
+        const started = new Date(session.started_at * 1000).toLocaleString([], {
+            dateStyle: 'short',
+            timeStyle: 'short',
         });
+        const ended = session.ended_at
+            ? new Date(session.ended_at * 1000).toLocaleTimeString([], { hour: '2-digit', minute: '2-digit' })
+            : t('sessions.open');
+        const row = document.createElement('p');
+        row.className = 'referral-hint';
+        row.textContent = t('sessions.row')
+            .replace('{start}', started)
+            .replace('{end}', ended)
+            .replace('{shared}', formatHours(session.sharing_seconds / 3600))
+            .replace('{discord}', formatHours(session.discord_seconds / 3600))
+            .replace('{ok}', session.heartbeats_ok)
+            .replace('{failed}', session.heartbeats_failed);
+        if (session.last_error) {
+            row.title = session.last_error;
+        }
+        elements.sessionsList.appendChild(row);
+    }
+}
+
+async function loadSessions() {
+    if (!elements.sessionsStart.value || !elements.sessionsEnd.value) {
+        const today = new Date();
+        const weekAgo = new Date(today);
+        weekAgo.setDate(today.getDate() - 6);
+        elements.sessionsStart.value = localDateInput(weekAgo);
+        elements.sessionsEnd.value = localDateInput(today);
+    }
+    try {
+        renderSessions(await invoke('get_sessions', {
+            startDate: elements.sessionsStart.value,
+            endDate: elements.sessionsEnd.value,
+        }));
     } catch (err) {
-        console.error('Failed to load Hackatime data:', err);
-        setStatValue(elements.currentProject, t('dashboard.stat_empty'));
-        setStatValue(elements.totalHours, t('dashboard.stat_empty'));
+        console.error('Failed to load sessions:', err);
+        elements.sessionsList.innerHTML = '';
+        elements.sessionsSummary.textContent = formatLoginError(t('sessions.load_failed'), err);
+        elements.sessionsSummary.classList.remove('hidden');
     }
 }
 
-async function sendFlavortownHeartbeat() {
+function renderHackatimeDataUnavailable() {
+    renderPublicName(null);
+    setStatValue(elements.currentProject, t('dashboard.stat_empty'));
+    setStatValue(elements.totalHours, t('dashboard.stat_empty'));
+    renderStaleNotice(null);
+}
+
+function renderStaleNotice(fetchedAt) {
+    if (!elements.statsStale) {
+        return;
+    }
+    if (!fetchedAt) {
+        elements.statsStale.classList.add('hidden');
+        return;
+    }
+    const time = new Date(fetchedAt * 1000).toLocaleTimeString([], { hour: '2-digit', minute: '2-digit' });
+    elements.statsStale.textContent = t('dashboard.stale').replace('{time}', time);
+    elements.statsStale.classList.remove('hidden');
+}
+
+async function ensureTrackingEventListeners() {
+    const eventApi = window.__TAURI__?.event;
+    if (!eventApi || typeof eventApi.listen !== 'function') {
+        return;
+    }
+
+    if (!hackatimeDataUnlisten) {
+        hackatimeDataUnlisten = await eventApi.listen('hackatime-data', (event) => {
+            renderHackatimeData(event?.payload);
+        });
+    }
+
+    if (!hackatimeDataFailedUnlisten) {
+        hackatimeDataFailedUnlisten = await eventApi.listen('hackatime-data-failed', (event) => {
+            console.error('Failed to load Hackatime data:', event?.payload);
+            renderHackatimeDataUnavailable();
+        });
+    }
+
+    if (!presenceProfilesUnlisten) {
+        presenceProfilesUnlisten = await eventApi.listen('presence-profiles', async () => {
+            try {
+                await populateSettings(await invoke('get_status'));
+            } catch (err) {
+                console.error('Failed to refresh presence profiles:', err);
+            }
+        });
+    }
+
+    if (!historyUnlisten) {
+        historyUnlisten = await eventApi.listen('history', (event) => {
+            renderHistory(event?.payload);
+        });
+    }
+
+    if (!heartbeatFailedUnlisten) {
+        heartbeatFailedUnlisten = await eventApi.listen('flavortown-heartbeat-failed', (event) => {
+            console.warn('Flavortown heartbeat failed:', event?.payload);
+            if (isUnauthorizedError(event?.payload)) {
+                promptRelogin();
+            }
+        });
+    }
+}
+
+function isUnauthorizedError(err) {
+    return err?.kind === 'unauthorized';
+}
+
+function promptRelogin() {
+    if (screens.hackatime.classList.contains('hidden') || authTransitionInProgress) {
+        return;
+    }
+    showScreen('login');
+    setLoginError(t('login.error_unauthorized'));
+    openApiLoginModal();
+}
+
+async function loadHackatimeData({ showLoading = false } = {}) {
+    if (showLoading) {
+        setHackatimeStatsLoading();
+    }
+
     try {
-        await invoke('send_flavortown_heartbeat');
+        await invoke('refresh_tracking');
     } catch (err) {
-        console.warn('Flavortown heartbeat failed:', err);
+        console.error('Failed to refresh Hackatime data:', err);
+        renderHackatimeDataUnavailable();
     }
 }
 
@@ -1120,6 +1722,8 @@ async function showHackatimeDashboard(status = null) {
     const nextStatus = status || await invoke('get_status');
     showScreen('hackatime');
     await populateSettings(nextStatus);
+    loadHistory();
+    loadSessions();
     await warmHackatimeData();
     await invoke('init_discord');
     await recoverRpcStatus();
@@ -1317,6 +1921,100 @@ elements.showTime.addEventListener('change', (event) => {
         });
 });
 
+elements.trackingBackend.addEventListener('change', () => {
+    updateTrackingFields();
+    if (elements.trackingBackend.value === 'hackatime') {
+        saveTrackingBackend();
+    }
+});
+
+elements.trackingSave.addEventListener('click', saveTrackingBackend);
+
+elements.presenceProjectHours.addEventListener('change', (event) => {
+    invoke('set_presence_project_hours', { source: event.target.value }).catch((err) => {
+        console.error('Error:', err);
+    });
+});
+
+elements.privacyAdd.addEventListener('click', () => addPrivacyRuleRow());
+elements.privacySave.addEventListener('click', savePrivacyRules);
+elements.sessionsLoad.addEventListener('click', loadSessions);
+elements.exportButton.addEventListener('click', exportHistory);
+
+for (const button of elements.dashboardTabs) {
+    button.addEventListener('click', () => showDashboardTab(button.dataset.tab));
+}
+
+for (const button of elements.statsRanges) {
+    button.addEventListener('click', () => {
+        statsRange = button.dataset.statsRange;
+        loadStats();
+    });
+}
+
+elements.presenceProfile.addEventListener('change', (event) => {
+    updatePresenceProfiles('activate_presence_profile', { name: event.target.value || null });
+});
+
+elements.profileCreate.addEventListener('click', () => {
+    updatePresenceProfiles('create_presence_profile', { name: elements.presenceProfileName.value });
+});
+
+elements.profileRename.addEventListener('click', () => {
+    updatePresenceProfiles('rename_presence_profile', {
+        name: elements.presenceProfile.value,
+        newName: elements.presenceProfileName.value,
+    });
+});
+
+elements.profileDelete.addEventListener('click', async () => {
+    const name = elements.presenceProfile.value;
+    if (name && await promptConfirm('profiles.confirm_delete')) {
+        updatePresenceProfiles('delete_presence_profile', { name });
+    }
+});
+
+elements.presenceFields.addEventListener('input', schedulePresencePreview);
+elements.presenceSave.addEventListener('click', () => savePresenceTemplate(readPresenceTemplate()));
+elements.presenceReset.addEventListener('click', () => savePresenceTemplate(null));
+
+elements.windowMode.addEventListener('change', () => {
+    updateWindowFields();
+    saveWindowMode();
+});
+
+elements.windowOffset.addEventListener('change', saveWindowMode);
+elements.windowDayStart.addEventListener('change', saveWindowMode);
+
+elements.dailyGoal.addEventListener('change', (event) => {
+    const minutes = Math.max(0, Math.min(1440, Math.round(Number(event.target.value) || 0)));
+    event.target.value = minutes;
+    invoke('set_daily_goal', { minutes })
+        .then(loadHistory)
+        .catch((err) => {
+            console.error('Error:', err);
+        });
+});
+
+elements.historyDays.addEventListener('change', (event) => {
+    const days = Math.max(1, Math.min(365, Math.round(Number(event.target.value) || 30)));
+    event.target.value = days;
+    invoke('set_history_days', { days })
+        .then(() => invoke('refresh_history'))
+        .then(renderHistory)
+        .catch((err) => {
+            console.error('Error:', err);
+        });
+});
+
+elements.maxStaleness.addEventListener('change', (event) => {
+    const minutes = Math.max(0, Math.min(1440, Math.round(Number(event.target.value) || 0)));
+    event.target.value = minutes;
+    invoke('set_max_snapshot_staleness', { minutes }).catch((err) => {
+        console.error('Error:', err);
+    });
+});
+
 elements.launchStartup.addEventListener('change', (event) => {
     const enabled = event.target.checked;
     invoke('set_launch_at_startup', { enabled }).catch((err) => {
@@ -1488,21 +2186,6 @@ document.addEventListener('keydown', (event) => {
 
 bindExternalLinks();
 
-async function minuteTick() {
-    try {
-        const status = await invoke('get_status');
-        if (status.auth_mode === 'hackatime') {
-            await loadHackatimeData();
-        }
-    } catch (err) {
-        console.error('Refresh error:', err);
-    }
-
-    await sendFlavortownHeartbeat();
-}
-
-setInterval(minuteTick, 60000);
-
 setInterval(() => {
     recoverRpcStatus({
         attempts: 4,
@@ -1518,5 +2201,7 @@ setInterval(() => {
     }
 }, UPDATER_RECHECK_INTERVAL_MS);
 
+ensureTrackingEventListeners().catch((err) => {
+    console.error('Tracking event listener init error:', err);
+});
 initApp();
-minuteTick();